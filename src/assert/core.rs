@@ -0,0 +1,337 @@
+//! Core functionality for assert command
+//!
+//! A rule names a field path in the syntax of [`crate::path`] and a check on
+//! its value:
+//!
+//! ```text
+//! package.edition == "2021"
+//! package.publish != true
+//! package.license exists
+//! package.build missing
+//! package.version matches ^1\.
+//! ```
+//!
+//! Values on the right of `==` and `!=` are TOML values, so strings are
+//! quoted. `matches` takes the rest of the line as a regular expression and
+//! tests it against strings, or against the TOML text of other values.
+
+use regex::Regex;
+use std::str::FromStr;
+use toml::{Table, Value as TomlValue};
+
+use crate::document::{load_toml, read_file};
+use crate::error::{Result, SourceSpan, TomlExtractError};
+use crate::flatten::inline_value;
+use crate::get::utils::get_nested_value;
+use crate::path::parse_path;
+
+/// What a rule checks about its field
+#[derive(Debug, Clone)]
+pub enum Check {
+    /// The field is present
+    Exists,
+    /// The field is absent
+    Missing,
+    /// The field equals the value
+    Equals(TomlValue),
+    /// The field is absent or differs from the value
+    NotEquals(TomlValue),
+    /// The field's text matches the regular expression
+    Matches(Regex),
+}
+
+/// One assertion about a field
+#[derive(Debug, Clone)]
+pub struct Rule {
+    /// Field path
+    pub path: String,
+    /// Check on the field's value
+    pub check: Check,
+}
+
+impl FromStr for Rule {
+    type Err = TomlExtractError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let rule = s.trim();
+        parse_rule(rule).map_err(|message| TomlExtractError::InvalidRule {
+            file: String::new(),
+            message,
+            span: SourceSpan::from_range(rule, 0..rule.len()),
+        })
+    }
+}
+
+/// A rule that did not hold
+#[derive(Debug, Clone, PartialEq)]
+pub struct Failure {
+    /// Field path of the rule
+    pub path: String,
+    /// Value found there, if any
+    pub actual: Option<TomlValue>,
+    /// What was wrong
+    pub message: String,
+}
+
+/// Read rules from a policy file, one per line
+pub fn read_policy(file_path: &str) -> Result<Vec<Rule>> {
+    let content = read_file(file_path)?;
+    parse_policy(&content, file_path)
+}
+
+/// Parse rules, one per line
+///
+/// Blank lines and lines starting with `#` are skipped. `file_path` is only
+/// used for error reporting.
+pub fn parse_policy(content: &str, file_path: &str) -> Result<Vec<Rule>> {
+    let mut rules = Vec::new();
+    let mut offset = 0;
+
+    for line in content.split_inclusive('\n') {
+        let range = offset..offset + line.trim_end().len();
+        offset += line.len();
+
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let rule = parse_rule(line).map_err(|message| TomlExtractError::InvalidRule {
+            file: file_path.to_string(),
+            message,
+            span: SourceSpan::from_range(content, range),
+        })?;
+        rules.push(rule);
+    }
+
+    Ok(rules)
+}
+
+/// Check rules against a TOML file, returning every failure
+pub fn assert_file(file_path: &str, rules: &[Rule]) -> Result<Vec<Failure>> {
+    let document = load_toml(file_path)?;
+    Ok(assert_value(&document, rules))
+}
+
+/// Check rules against a document, returning every failure in rule order
+///
+/// A path that cannot be followed, such as a key below a string, fails
+/// whatever the check.
+pub fn assert_value(document: &TomlValue, rules: &[Rule]) -> Vec<Failure> {
+    rules
+        .iter()
+        .filter_map(|rule| {
+            let actual = match get_nested_value(document, &rule.path) {
+                Ok(value) => Some(value),
+                Err(e) if e.is_not_found() => None,
+                Err(e) => {
+                    return Some(Failure {
+                        path: rule.path.clone(),
+                        actual: None,
+                        message: unfollowable(e),
+                    })
+                }
+            };
+            check_value(&rule.check, actual).map(|message| Failure {
+                path: rule.path.clone(),
+                actual: actual.cloned(),
+                message,
+            })
+        })
+        .collect()
+}
+
+/// Render failures as `file: path: message` lines
+pub fn render_failures(file_path: &str, failures: &[Failure]) -> String {
+    failures
+        .iter()
+        .map(|failure| format!("{}: {}: {}\n", file_path, failure.path, failure.message))
+        .collect()
+}
+
+/// Explain why a path cannot be followed
+fn unfollowable(error: TomlExtractError) -> String {
+    match error {
+        TomlExtractError::TypeMismatch { path, found, .. } => {
+            format!("cannot follow the path: `{}` is a {}", path, found)
+        }
+        TomlExtractError::NotAnArray(path) => {
+            format!("cannot follow the path: `{}` is not an array", path)
+        }
+        other => other.to_string(),
+    }
+}
+
+/// Why `actual` fails the check, if it does
+fn check_value(check: &Check, actual: Option<&TomlValue>) -> Option<String> {
+    let found = || match actual {
+        Some(value) => format!("found {}", inline_value(value)),
+        None => "missing".to_string(),
+    };
+    match check {
+        Check::Exists => actual.is_none().then(|| "missing".to_string()),
+        Check::Missing => {
+            actual.map(|value| format!("should be missing, found {}", inline_value(value)))
+        }
+        Check::Equals(expected) => (actual != Some(expected))
+            .then(|| format!("expected {}, {}", inline_value(expected), found())),
+        Check::NotEquals(unwanted) => {
+            (actual == Some(unwanted)).then(|| format!("must not be {}", inline_value(unwanted)))
+        }
+        Check::Matches(regex) => {
+            let matched = actual.is_some_and(|value| match value {
+                TomlValue::String(s) => regex.is_match(s),
+                other => regex.is_match(&inline_value(other)),
+            });
+            (!matched).then(|| format!("{} does not match {}", found(), regex.as_str()))
+        }
+    }
+}
+
+fn parse_rule(text: &str) -> std::result::Result<Rule, String> {
+    let text = text.trim();
+    let (path, rest) = split_path(text);
+    if path.is_empty() {
+        return Err("expected a field path".to_string());
+    }
+    parse_path(path).map_err(|e| e.to_string())?;
+
+    let check = if let Some(raw) = rest.strip_prefix("==") {
+        Check::Equals(parse_value(raw)?)
+    } else if let Some(raw) = rest.strip_prefix("!=") {
+        Check::NotEquals(parse_value(raw)?)
+    } else {
+        let (word, argument) = rest
+            .split_once(char::is_whitespace)
+            .map_or((rest, ""), |(word, argument)| (word, argument.trim()));
+        match (word, argument) {
+            ("exists", "") => Check::Exists,
+            ("missing", "") => Check::Missing,
+            ("matches", "") => {
+                return Err("expected a regular expression after `matches`".to_string())
+            }
+            ("matches", pattern) => Check::Matches(
+                Regex::new(pattern).map_err(|e| format!("invalid regular expression: {}", e))?,
+            ),
+            _ => {
+                return Err(
+                    "expected `==`, `!=`, `exists`, `missing` or `matches` after the path"
+                        .to_string(),
+                )
+            }
+        }
+    };
+
+    Ok(Rule {
+        path: path.to_string(),
+        check,
+    })
+}
+
+/// Split off the field path, which ends at whitespace or an operator outside quotes
+fn split_path(text: &str) -> (&str, &str) {
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (Some('"'), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            }
+            (Some(q), c) if c == q && !escaped => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '=' | '!') => return (&text[..i], &text[i..]),
+            (None, c) if c.is_whitespace() => return (&text[..i], text[i..].trim_start()),
+            _ => {}
+        }
+        escaped = false;
+    }
+    (text, "")
+}
+
+fn parse_value(raw: &str) -> std::result::Result<TomlValue, String> {
+    let raw = raw.trim();
+    toml::from_str::<Table>(&format!("value = {}", raw))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .ok_or_else(|| format!("invalid TOML value `{}` (strings need quotes)", raw))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"
+[package]
+name = "demo"
+version = "0.3.0"
+edition = "2018"
+publish = false
+keywords = ["toml", "cli"]
+
+[package.metadata."my tool"]
+level = 2
+"#;
+
+    fn failures(policy: &str) -> Vec<Failure> {
+        let document: TomlValue = toml::from_str(SAMPLE).unwrap();
+        assert_value(&document, &parse_policy(policy, "policy.txt").unwrap())
+    }
+
+    #[test]
+    fn test_passing_rules() {
+        let policy = r#"
+# every rule here holds
+package.name == "demo"
+package.publish==false
+package.keywords == ["toml", "cli"]
+package.edition != "2021"
+package.license missing
+package.keywords[1] exists
+package.metadata."my tool".level == 2
+package.version matches ^0\.\d+
+package.publish matches ^false$
+"#;
+        assert_eq!(failures(policy), []);
+    }
+
+    #[test]
+    fn test_failures_report_actual_values() {
+        let messages: Vec<String> = failures(
+            "package.edition == \"2021\"\npackage.license exists\npackage.version matches ^1\\.\npackage.publish missing\npackage.name != \"demo\"\npackage.name[0] exists\npackage.name.x missing\npackage.nope.x missing",
+        )
+        .iter()
+        .map(|failure| format!("{}: {}", failure.path, failure.message))
+        .collect();
+        assert_eq!(
+            messages,
+            [
+                "package.edition: expected \"2021\", found \"2018\"",
+                "package.license: missing",
+                "package.version: found \"0.3.0\" does not match ^1\\.",
+                "package.publish: should be missing, found false",
+                "package.name: must not be \"demo\"",
+                "package.name[0]: cannot follow the path: `package.name` is not an array",
+                "package.name.x: cannot follow the path: `package.name` is a string",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        assert!("package.edition == 2021-".parse::<Rule>().is_err());
+        assert!("package.edition is 2021".parse::<Rule>().is_err());
+        assert!("package.version matches (".parse::<Rule>().is_err());
+        assert!("package..version exists".parse::<Rule>().is_err());
+
+        let direct = "b == bare".parse::<Rule>().unwrap_err();
+        let from_file = parse_policy("a exists\n\nb == bare\n", "policy.txt").unwrap_err();
+        assert_eq!(direct.exit_code(), from_file.exit_code());
+        match from_file {
+            TomlExtractError::InvalidRule { span, .. } => {
+                assert_eq!(span.line_text, "b == bare");
+                assert_eq!(span.line, 3);
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+}
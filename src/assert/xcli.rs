@@ -0,0 +1,64 @@
+//! CLI definitions and handling for assert command
+
+use clap::{Arg, ArgMatches, Command};
+
+use super::core::*;
+use crate::error::Result;
+
+/// Define the assert command CLI structure
+pub fn assert_command() -> Command {
+    Command::new("assert")
+        .about("Check rules such as `package.edition == \"2021\"` and report every one that fails")
+        .arg(
+            Arg::new("rules")
+                .value_name("RULE")
+                .help("Rule: PATH == VALUE, PATH != VALUE, PATH exists, PATH missing or PATH matches REGEX")
+                .num_args(1..)
+                .required_unless_present("policy"),
+        )
+        .arg(
+            Arg::new("file")
+                .short('f')
+                .long("file")
+                .value_name("FILE")
+                .help("TOML file path")
+                .default_value("Cargo.toml"),
+        )
+        .arg(
+            Arg::new("policy")
+                .short('p')
+                .long("policy")
+                .value_name("POLICY")
+                .help("File of rules, one per line; # starts a comment")
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            Arg::new("quiet")
+                .short('q')
+                .long("quiet")
+                .help("Print nothing (the exit status still reports failures)")
+                .action(clap::ArgAction::SetTrue),
+        )
+}
+
+/// Handle assert command logic
+///
+/// Returns whether every rule held; the caller turns `Ok(false)` into exit
+/// status 1.
+pub fn handle_assert_command(matches: &ArgMatches) -> Result<bool> {
+    let file_path = matches.get_one::<String>("file").unwrap();
+
+    let mut rules = Vec::new();
+    for policy_path in matches.get_many::<String>("policy").into_iter().flatten() {
+        rules.extend(read_policy(policy_path)?);
+    }
+    for rule in matches.get_many::<String>("rules").into_iter().flatten() {
+        rules.push(rule.parse::<Rule>()?);
+    }
+
+    let failures = assert_file(file_path, &rules)?;
+    if !matches.get_flag("quiet") {
+        print!("{}", render_failures(file_path, &failures));
+    }
+    Ok(failures.is_empty())
+}
@@ -0,0 +1,329 @@
+//! Core functionality for codegen command
+//!
+//! Code is generated from the [`Shape`] of one or more sample documents, so
+//! a field is only required if every sample has it. Tables become structs
+//! named after their key, arrays become `Vec`s of their element type, and
+//! places where samples disagree on the type fall back to `toml::Value`.
+
+use std::fmt::Write as _;
+
+use crate::error::{Result, TomlExtractError};
+use crate::path::{parse_path, PathSegment};
+use crate::schema::{infer_files, Shape};
+
+/// Generate serde structs for `field_path` (the whole document if empty) across sample files
+///
+/// The root struct is called `root_name`, or else after the last key of
+/// `field_path` (`Config` for the whole document).
+///
+/// # Errors
+/// `field_path` must be a table in every sample.
+pub fn rust_structs_for_files(
+    file_paths: &[String],
+    field_path: &str,
+    root_name: Option<&str>,
+) -> Result<String> {
+    let shape = infer_files(file_paths, field_path)?;
+    if shape.types != ["table"] {
+        return Err(TomlExtractError::TypeMismatch {
+            path: field_path.to_string(),
+            expected: "table".to_string(),
+            found: shape.types.join(" or "),
+        });
+    }
+    let root_name = match root_name {
+        Some(name) => name.to_string(),
+        None => match parse_path(field_path)?.last() {
+            Some(PathSegment::Key(key)) => type_name(key),
+            _ => "Config".to_string(),
+        },
+    };
+    Ok(rust_structs(&shape, &root_name))
+}
+
+/// Generate serde structs for a table shape, starting with `root_name`
+///
+/// Struct names that would shadow a prelude type (`String`, `Vec`, ...)
+/// are qualified with their parent's name, the root's with `Root`.
+pub fn rust_structs(shape: &Shape, root_name: &str) -> String {
+    let mut generator = RustGenerator {
+        names: RESERVED_TYPES.iter().map(|name| name.to_string()).collect(),
+        ..RustGenerator::default()
+    };
+    let root_name = generator.unique_name(root_name.to_string(), "Root");
+    generator.emit_struct(shape, &root_name);
+
+    let mut out = String::from("use serde::{Deserialize, Serialize};\n");
+    for definition in &generator.structs {
+        out.push('\n');
+        out.push_str(definition);
+    }
+    out
+}
+
+#[derive(Default)]
+struct RustGenerator {
+    /// Struct definitions, parents before their children
+    structs: Vec<String>,
+    names: Vec<String>,
+}
+
+impl RustGenerator {
+    fn emit_struct(&mut self, shape: &Shape, name: &str) {
+        let slot = self.structs.len();
+        self.structs.push(String::new());
+
+        let mut out = String::new();
+        writeln!(out, "#[derive(Debug, Clone, Deserialize, Serialize)]").unwrap();
+        writeln!(out, "pub struct {} {{", name).unwrap();
+        let mut fields: Vec<String> = Vec::new();
+        for (key, child) in &shape.properties {
+            // `log-level` and `log_level` would both be `log_level`
+            let mut field = field_name(key);
+            let mut suffix = 2;
+            while fields.contains(&field) {
+                field = format!("{}_{}", field_name(key).trim_start_matches("r#"), suffix);
+                suffix += 1;
+            }
+            fields.push(field.clone());
+            let mut attributes = Vec::new();
+            if field.trim_start_matches("r#") != key {
+                attributes.push(format!("rename = {:?}", key));
+            }
+            let mut field_type = self.rust_type(child, key, name);
+            if !shape.is_required(child) {
+                attributes.push("skip_serializing_if = \"Option::is_none\"".to_string());
+                field_type = format!("Option<{}>", field_type);
+            }
+            if !attributes.is_empty() {
+                writeln!(out, "    #[serde({})]", attributes.join(", ")).unwrap();
+            }
+            writeln!(out, "    pub {}: {},", field, field_type).unwrap();
+        }
+        out.push_str("}\n");
+
+        self.structs[slot] = out;
+    }
+
+    fn rust_type(&mut self, shape: &Shape, key: &str, parent: &str) -> String {
+        let mut types = shape.types.clone();
+        if types.contains(&"float") {
+            types.retain(|name| *name != "integer");
+        }
+
+        match types.as_slice() {
+            ["string"] => "String".to_string(),
+            ["integer"] => "i64".to_string(),
+            ["float"] => "f64".to_string(),
+            ["boolean"] => "bool".to_string(),
+            ["datetime"] => "toml::value::Datetime".to_string(),
+            ["array"] => match shape.items.as_deref().filter(|items| items.count > 0) {
+                Some(items) => format!("Vec<{}>", self.rust_type(items, key, parent)),
+                None => "Vec<toml::Value>".to_string(),
+            },
+            ["table"] if shape.properties.is_empty() => "toml::Table".to_string(),
+            ["table"] => {
+                let name = self.unique_name(type_name(key), parent);
+                self.emit_struct(shape, &name);
+                name
+            }
+            _ => "toml::Value".to_string(),
+        }
+    }
+
+    /// Qualify a struct name with its parent's when it is already taken
+    fn unique_name(&mut self, name: String, parent: &str) -> String {
+        let mut candidate = name.clone();
+        let mut suffix = 1;
+        while self.names.contains(&candidate) {
+            candidate = match suffix {
+                1 => format!("{}{}", parent, name),
+                n => format!("{}{}{}", parent, name, n),
+            };
+            suffix += 1;
+        }
+        self.names.push(candidate.clone());
+        candidate
+    }
+}
+
+/// Names the generated code relies on, which a struct must not shadow
+const RESERVED_TYPES: &[&str] = &[
+    "Self",
+    "String",
+    "Option",
+    "Some",
+    "None",
+    "Vec",
+    "Box",
+    "Result",
+    "Ok",
+    "Err",
+    "Deserialize",
+    "Serialize",
+];
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+/// `snake_case` field name for a key; keywords become raw identifiers
+fn field_name(key: &str) -> String {
+    let mut name = String::new();
+    let mut previous: Option<char> = None;
+    for c in key.chars() {
+        if c.is_ascii_uppercase() {
+            if previous.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit()) {
+                name.push('_');
+            }
+            name.push(c.to_ascii_lowercase());
+        } else if c.is_ascii_alphanumeric() {
+            name.push(c);
+        } else {
+            name.push('_');
+        }
+        previous = Some(c);
+    }
+
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    match name.as_str() {
+        // Not allowed as raw identifiers
+        "self" | "super" | "crate" | "_" => format!("{}_", name),
+        keyword if KEYWORDS.contains(&keyword) => format!("r#{}", name),
+        _ => name,
+    }
+}
+
+/// `PascalCase` type name for a key
+fn type_name(key: &str) -> String {
+    let mut name = String::new();
+    for part in key.split(|c: char| !c.is_ascii_alphanumeric()) {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            name.push(first.to_ascii_uppercase());
+            name.extend(chars);
+        }
+    }
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert_str(0, "Table");
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::infer_shape;
+    use toml::Value as TomlValue;
+
+    #[test]
+    fn test_rust_structs() {
+        let samples: Vec<TomlValue> = [
+            "log-level = \"info\"\nworkers = 4\ntype = \"api\"\nstarted = 2024-05-01\n\n[server]\nhost = \"0.0.0.0\"\nport = 80\n\n[[route]]\npath = \"/\"\n[[route]]\npath = \"/x\"\nweight = 0.5",
+            "log-level = \"debug\"\nworkers = 2.5\ntype = \"job\"\nstarted = 2024-05-02\nextra = {}\n\n[server]\nhost = \"localhost\"\nport = 8080\ntls = { cert = \"a.pem\" }",
+        ]
+        .iter()
+        .map(|s| toml::from_str(s).unwrap())
+        .collect();
+
+        assert_eq!(
+            rust_structs(&infer_shape(&samples), "MyTool"),
+            r#"use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MyTool {
+    #[serde(rename = "log-level")]
+    pub log_level: String,
+    pub workers: f64,
+    pub r#type: String,
+    pub started: toml::value::Datetime,
+    pub server: Server,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub route: Option<Vec<Route>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extra: Option<toml::Table>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Server {
+    pub host: String,
+    pub port: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls: Option<Tls>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Tls {
+    pub cert: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Route {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f64>,
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(field_name("camelCase"), "camel_case");
+        assert_eq!(field_name("2fa"), "_2fa");
+        assert_eq!(field_name("self"), "self_");
+        assert_eq!(field_name("match"), "r#match");
+        assert_eq!(type_name("build-dependencies"), "BuildDependencies");
+        assert_eq!(type_name("mytool"), "Mytool");
+
+        let shape = infer_shape([
+            &toml::from_str::<TomlValue>("[a.item]\nx = 1\n[b.item]\ny = 2").unwrap(),
+        ]);
+        let code = rust_structs(&shape, "Config");
+        assert!(code.contains("pub struct Item {") && code.contains("pub struct BItem {"));
+    }
+
+    #[test]
+    fn test_name_clashes() {
+        let shape = infer_shape([&toml::from_str::<TomlValue>(
+            "log-level = 1\nlog_level = 2\n[string]\nvalue = \"x\"\n[vec.option]\ny = 1",
+        )
+        .unwrap()]);
+        assert_eq!(
+            rust_structs(&shape, "String"),
+            r#"use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RootString {
+    #[serde(rename = "log-level")]
+    pub log_level: i64,
+    #[serde(rename = "log_level")]
+    pub log_level_2: i64,
+    pub string: RootStringString,
+    pub vec: RootStringVec,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RootStringString {
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RootStringVec {
+    pub option: RootStringVecOption,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RootStringVecOption {
+    pub y: i64,
+}
+"#
+        );
+    }
+}
@@ -0,0 +1,72 @@
+//! CLI definitions and handling for codegen command
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+
+use super::core::*;
+use crate::document::write_file;
+use crate::error::Result;
+
+/// Define the codegen command CLI structure
+pub fn codegen_command() -> Command {
+    Command::new("codegen")
+        .about("Generate code that mirrors the structure of TOML files")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("rust")
+                .about("Generate serde structs; fields missing from some samples become Option")
+                .arg(
+                    Arg::new("file")
+                        .short('f')
+                        .long("file")
+                        .value_name("FILE")
+                        .help("Sample TOML file (repeat for several samples)")
+                        .action(ArgAction::Append)
+                        .default_value("Cargo.toml"),
+                )
+                .arg(
+                    Arg::new("field")
+                        .short('k')
+                        .long("field")
+                        .value_name("FIELD")
+                        .help("Field path of the table to mirror (the whole document if omitted)")
+                        .default_value(""),
+                )
+                .arg(
+                    Arg::new("name").long("name").value_name("NAME").help(
+                        "Name of the root struct (default: the last key of FIELD, or Config)",
+                    ),
+                )
+                .arg(
+                    Arg::new("output-file")
+                        .short('o')
+                        .long("output-file")
+                        .value_name("FILE")
+                        .help("Write the code to FILE instead of stdout"),
+                ),
+        )
+}
+
+/// Handle codegen command logic
+pub fn handle_codegen_command(matches: &ArgMatches) -> Result<()> {
+    let Some(("rust", matches)) = matches.subcommand() else {
+        unreachable!("a subcommand is required");
+    };
+    let files: Vec<String> = matches
+        .get_many::<String>("file")
+        .unwrap()
+        .cloned()
+        .collect();
+    let output = rust_structs_for_files(
+        &files,
+        matches.get_one::<String>("field").unwrap(),
+        matches.get_one::<String>("name").map(String::as_str),
+    )?;
+
+    match matches.get_one::<String>("output-file") {
+        Some(output_file) => write_file(output_file, &output),
+        None => {
+            print!("{}", output);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,375 @@
+//! Core functionality for convert command
+//!
+//! Every conversion goes through a TOML value: the input document is read
+//! into a [`TomlValue`] and written back out in the target format. Key order
+//! is kept in both directions. TOML datetimes have no JSON or YAML
+//! counterpart, so they are written as `{"$datetime": "..."}` objects (or
+//! plain strings) and such objects are turned back into datetimes on input.
+
+use serde_json::Value as JsonValue;
+use std::path::Path;
+use std::str::FromStr;
+use toml::Value as TomlValue;
+
+#[cfg(feature = "yaml")]
+use crate::document::parse_yaml;
+use crate::document::{parse_json, parse_toml, read_file};
+use crate::error::{Result, TomlExtractError};
+use crate::get::utils::{to_json_value_with, type_name};
+
+/// Key of the single-entry object that tags a datetime in JSON and YAML
+pub const DATETIME_TAG: &str = "$datetime";
+
+/// Document syntax accepted and produced by [`convert_str`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentFormat {
+    /// TOML
+    Toml,
+    /// JSON
+    Json,
+    /// YAML (requires the `yaml` feature)
+    Yaml,
+}
+
+impl DocumentFormat {
+    /// Guess the format from a file extension, defaulting to TOML
+    pub fn from_path(path: &str) -> Self {
+        match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Self::Json,
+            Some("yaml" | "yml") => Self::Yaml,
+            _ => Self::Toml,
+        }
+    }
+}
+
+impl FromStr for DocumentFormat {
+    type Err = TomlExtractError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "toml" => Ok(Self::Toml),
+            "json" => Ok(Self::Json),
+            "yaml" | "yml" => Ok(Self::Yaml),
+            other => Err(TomlExtractError::InvalidArgument(format!(
+                "Unknown document format: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// What to do with JSON/YAML `null`, which TOML cannot represent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NullHandling {
+    /// Drop the key (or array element)
+    Omit,
+    /// Fail with [`TomlExtractError::TypeMismatch`]
+    Error,
+    /// Replace with `""`
+    EmptyString,
+}
+
+impl FromStr for NullHandling {
+    type Err = TomlExtractError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "omit" => Ok(Self::Omit),
+            "error" => Ok(Self::Error),
+            "empty-string" => Ok(Self::EmptyString),
+            other => Err(TomlExtractError::InvalidArgument(format!(
+                "Unknown null handling: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// How TOML datetimes are written to JSON and YAML
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatetimeStyle {
+    /// `{"$datetime": "1979-05-27T07:32:00Z"}`, converted back on input
+    Tagged,
+    /// A plain string; the datetime type is lost
+    String,
+}
+
+impl FromStr for DatetimeStyle {
+    type Err = TomlExtractError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "tagged" => Ok(Self::Tagged),
+            "string" => Ok(Self::String),
+            other => Err(TomlExtractError::InvalidArgument(format!(
+                "Unknown datetime style: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Options for [`convert_str`]
+#[derive(Debug, Clone)]
+pub struct ConvertOptions {
+    /// Input syntax
+    pub from: DocumentFormat,
+    /// Output syntax
+    pub to: DocumentFormat,
+    /// Handling of `null` on input
+    pub null_handling: NullHandling,
+    /// Representation of datetimes on output
+    pub datetime_style: DatetimeStyle,
+    /// Write JSON on a single line
+    pub compact: bool,
+}
+
+impl Default for ConvertOptions {
+    fn default() -> Self {
+        Self {
+            from: DocumentFormat::Toml,
+            to: DocumentFormat::Json,
+            null_handling: NullHandling::Error,
+            datetime_style: DatetimeStyle::Tagged,
+            compact: false,
+        }
+    }
+}
+
+/// Convert a file, reading it in `options.from` syntax
+pub fn convert_file(file_path: &str, options: &ConvertOptions) -> Result<String> {
+    let content = read_file(file_path)?;
+    let value = parse_document(&content, file_path, options)?;
+    render_document(&value, options)
+}
+
+/// Convert document text from `options.from` to `options.to` syntax
+pub fn convert_str(content: &str, options: &ConvertOptions) -> Result<String> {
+    let value = parse_document(content, "<input>", options)?;
+    render_document(&value, options)
+}
+
+/// Parse a document in `options.from` syntax into a TOML value
+///
+/// # Errors
+/// JSON and YAML documents must have an object at the top level.
+pub fn parse_document(
+    content: &str,
+    file_path: &str,
+    options: &ConvertOptions,
+) -> Result<TomlValue> {
+    let json_value: JsonValue = match options.from {
+        DocumentFormat::Toml => return parse_toml(content, file_path),
+        DocumentFormat::Json => parse_json(content, file_path)?,
+        #[cfg(feature = "yaml")]
+        DocumentFormat::Yaml => parse_yaml(content, file_path)?,
+        #[cfg(not(feature = "yaml"))]
+        DocumentFormat::Yaml => return Err(yaml_disabled()),
+    };
+
+    match json_to_toml_value(&json_value, "", options.null_handling)? {
+        Some(value @ TomlValue::Table(_)) => Ok(value),
+        Some(other) => Err(TomlExtractError::TypeMismatch {
+            path: String::new(),
+            expected: "table".to_string(),
+            found: type_name(&other).to_string(),
+        }),
+        None => Ok(TomlValue::Table(Default::default())),
+    }
+}
+
+/// Render a TOML value in `options.to` syntax
+pub fn render_document(value: &TomlValue, options: &ConvertOptions) -> Result<String> {
+    if options.to == DocumentFormat::Toml {
+        return Ok(toml::to_string(value)?);
+    }
+
+    let json_value = toml_to_json_value(value, options.datetime_style)?;
+
+    match options.to {
+        DocumentFormat::Json if options.compact => Ok(serde_json::to_string(&json_value)? + "\n"),
+        DocumentFormat::Json => Ok(serde_json::to_string_pretty(&json_value)? + "\n"),
+        #[cfg(feature = "yaml")]
+        DocumentFormat::Yaml => Ok(serde_yaml::to_string(&json_value)?),
+        #[cfg(not(feature = "yaml"))]
+        DocumentFormat::Yaml => Err(yaml_disabled()),
+        DocumentFormat::Toml => unreachable!("handled above"),
+    }
+}
+
+/// Convert a TOML value to JSON, writing datetimes in the given style
+pub fn toml_to_json_value(value: &TomlValue, datetime_style: DatetimeStyle) -> Result<JsonValue> {
+    match datetime_style {
+        DatetimeStyle::Tagged => to_json_value_with(value, &|dt| {
+            let mut tagged = serde_json::Map::new();
+            tagged.insert(DATETIME_TAG.to_string(), JsonValue::String(dt.to_string()));
+            JsonValue::Object(tagged)
+        }),
+        DatetimeStyle::String => to_json_value_with(value, &|dt| JsonValue::String(dt.to_string())),
+    }
+}
+
+/// Convert a JSON value to TOML, returning `None` for an omitted `null`
+///
+/// `path` is the location of `value`, used in error messages.
+pub fn json_to_toml_value(
+    value: &JsonValue,
+    path: &str,
+    null_handling: NullHandling,
+) -> Result<Option<TomlValue>> {
+    let converted = match value {
+        JsonValue::Null => match null_handling {
+            NullHandling::Omit => return Ok(None),
+            NullHandling::EmptyString => TomlValue::String(String::new()),
+            NullHandling::Error => {
+                return Err(TomlExtractError::TypeMismatch {
+                    path: path.to_string(),
+                    expected: "non-null value".to_string(),
+                    found: "null".to_string(),
+                })
+            }
+        },
+        JsonValue::Bool(b) => TomlValue::Boolean(*b),
+        JsonValue::Number(n) if n.is_f64() => TomlValue::Float(n.as_f64().unwrap_or(f64::NAN)),
+        JsonValue::Number(n) => match n.as_i64() {
+            Some(i) => TomlValue::Integer(i),
+            // TOML integers are 64-bit signed; a float would silently lose digits
+            None => {
+                return Err(TomlExtractError::TypeMismatch {
+                    path: path.to_string(),
+                    expected: "integer between -2^63 and 2^63-1".to_string(),
+                    found: n.to_string(),
+                })
+            }
+        },
+        JsonValue::String(s) => TomlValue::String(s.clone()),
+        JsonValue::Array(array) => {
+            let mut elements = Vec::with_capacity(array.len());
+            for (index, elem) in array.iter().enumerate() {
+                let elem_path = format!("{}[{}]", path, index);
+                elements.extend(json_to_toml_value(elem, &elem_path, null_handling)?);
+            }
+            TomlValue::Array(elements)
+        }
+        JsonValue::Object(object) => {
+            if let Some(datetime) = tagged_datetime(object) {
+                return Ok(Some(TomlValue::Datetime(datetime)));
+            }
+            let mut table = toml::map::Map::new();
+            for (key, child) in object {
+                let child_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                if let Some(converted) = json_to_toml_value(child, &child_path, null_handling)? {
+                    table.insert(key.clone(), converted);
+                }
+            }
+            TomlValue::Table(table)
+        }
+    };
+    Ok(Some(converted))
+}
+
+/// Recognize `{"$datetime": "..."}` holding a valid TOML datetime
+pub(crate) fn tagged_datetime(
+    object: &serde_json::Map<String, JsonValue>,
+) -> Option<toml::value::Datetime> {
+    if object.len() != 1 {
+        return None;
+    }
+    object.get(DATETIME_TAG)?.as_str()?.parse().ok()
+}
+
+#[cfg(not(feature = "yaml"))]
+fn yaml_disabled() -> TomlExtractError {
+    TomlExtractError::InvalidArgument("YAML conversion requires the `yaml` feature".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(from: DocumentFormat, to: DocumentFormat) -> ConvertOptions {
+        ConvertOptions {
+            from,
+            to,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_toml_json_round_trip() {
+        let source = "title = \"demo\"\nreleased = 1979-05-27T07:32:00Z\n\n[package]\nname = \"b\"\nversion = \"1.0.0\"\n";
+        let json =
+            convert_str(source, &options(DocumentFormat::Toml, DocumentFormat::Json)).unwrap();
+        assert!(json.contains("\"$datetime\": \"1979-05-27T07:32:00Z\""));
+        assert!(json.find("\"name\"").unwrap() < json.find("\"version\"").unwrap());
+
+        let back =
+            convert_str(&json, &options(DocumentFormat::Json, DocumentFormat::Toml)).unwrap();
+        assert_eq!(back, source);
+    }
+
+    #[test]
+    fn test_json_null_handling() {
+        let source = r#"{"a": 1, "b": null, "c": [1, null, 2]}"#;
+        let mut opts = options(DocumentFormat::Json, DocumentFormat::Toml);
+
+        opts.null_handling = NullHandling::Omit;
+        assert_eq!(convert_str(source, &opts).unwrap(), "a = 1\nc = [1, 2]\n");
+
+        opts.null_handling = NullHandling::EmptyString;
+        assert_eq!(
+            convert_str(source, &opts).unwrap(),
+            "a = 1\nb = \"\"\nc = [1, \"\", 2]\n"
+        );
+
+        opts.null_handling = NullHandling::Error;
+        match convert_str(source, &opts).unwrap_err() {
+            TomlExtractError::TypeMismatch { path, .. } => assert_eq!(path, "b"),
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_input_errors() {
+        let opts = options(DocumentFormat::Json, DocumentFormat::Toml);
+        match convert_str("{\n  \"a\": 1,\n}", &opts).unwrap_err() {
+            error @ TomlExtractError::InvalidInput { .. } => {
+                assert_eq!(error.exit_code(), 2);
+                assert!(error
+                    .to_string()
+                    .starts_with("Invalid JSON syntax in <input>:3:1"));
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+        match convert_str(r#"{"big": [18446744073709551615]}"#, &opts).unwrap_err() {
+            TomlExtractError::TypeMismatch { path, found, .. } => {
+                assert_eq!(path, "big[0]");
+                assert_eq!(found, "18446744073709551615");
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+
+        #[cfg(feature = "yaml")]
+        {
+            let opts = options(DocumentFormat::Yaml, DocumentFormat::Toml);
+            let error = convert_str("a: 1\nb: [1\n", &opts).unwrap_err();
+            assert!(matches!(error, TomlExtractError::InvalidInput { .. }));
+            assert_eq!(error.exit_code(), 2);
+        }
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_yaml_to_toml() {
+        let source = "package:\n  name: demo\n  keywords: [toml, cli]\nedition: '2021'\n";
+        let toml =
+            convert_str(source, &options(DocumentFormat::Yaml, DocumentFormat::Toml)).unwrap();
+        let value: TomlValue = toml::from_str(&toml).unwrap();
+        assert_eq!(value["package"]["keywords"][1].as_str(), Some("cli"));
+        assert_eq!(value["edition"].as_str(), Some("2021"));
+    }
+}
@@ -0,0 +1,10 @@
+//! Conversion between TOML, JSON and YAML documents
+
+pub mod core;
+#[cfg(feature = "cli")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cli")))]
+pub mod xcli;
+
+pub use core::*;
+#[cfg(feature = "cli")]
+pub use xcli::*;
@@ -0,0 +1,91 @@
+//! CLI definitions and handling for convert command
+
+use clap::{Arg, ArgMatches, Command};
+
+use super::core::*;
+use crate::document::write_file;
+use crate::error::Result;
+
+/// Define the convert command CLI structure
+pub fn convert_command() -> Command {
+    Command::new("convert")
+        .about("Convert a document between TOML, JSON and YAML")
+        .arg(
+            Arg::new("file")
+                .short('f')
+                .long("file")
+                .value_name("FILE")
+                .help("Input file path")
+                .default_value("Cargo.toml"),
+        )
+        .arg(
+            Arg::new("from")
+                .long("from")
+                .value_name("FORMAT")
+                .help("Input format (toml, json, yaml); guessed from the file extension by default")
+                .value_parser(["toml", "json", "yaml"]),
+        )
+        .arg(
+            Arg::new("to")
+                .short('t')
+                .long("to")
+                .value_name("FORMAT")
+                .help("Output format (toml, json, yaml)")
+                .value_parser(["toml", "json", "yaml"])
+                .required(true),
+        )
+        .arg(
+            Arg::new("null")
+                .long("null")
+                .value_name("MODE")
+                .help("Handling of JSON/YAML null, which TOML lacks (omit, error, empty-string)")
+                .value_parser(["omit", "error", "empty-string"])
+                .default_value("error"),
+        )
+        .arg(
+            Arg::new("datetime")
+                .long("datetime")
+                .value_name("STYLE")
+                .help("Datetime output: tagged as {\"$datetime\": \"...\"} (read back as a datetime) or a plain string")
+                .value_parser(["tagged", "string"])
+                .default_value("tagged"),
+        )
+        .arg(
+            Arg::new("compact")
+                .long("compact")
+                .help("Write JSON on a single line")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("output-file")
+                .short('o')
+                .long("output-file")
+                .value_name("FILE")
+                .help("Write the result to FILE instead of stdout"),
+        )
+}
+
+/// Handle convert command logic
+pub fn handle_convert_command(matches: &ArgMatches) -> Result<()> {
+    let file_path = matches.get_one::<String>("file").unwrap();
+    let from = match matches.get_one::<String>("from") {
+        Some(from) => from.parse()?,
+        None => DocumentFormat::from_path(file_path),
+    };
+    let options = ConvertOptions {
+        from,
+        to: matches.get_one::<String>("to").unwrap().parse()?,
+        null_handling: matches.get_one::<String>("null").unwrap().parse()?,
+        datetime_style: matches.get_one::<String>("datetime").unwrap().parse()?,
+        compact: matches.get_flag("compact"),
+    };
+
+    let output = convert_file(file_path, &options)?;
+    match matches.get_one::<String>("output-file") {
+        Some(output_file) => write_file(output_file, &output),
+        None => {
+            print!("{}", output);
+            Ok(())
+        }
+    }
+}
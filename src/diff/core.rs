@@ -0,0 +1,232 @@
+//! Core functionality for diff command
+//!
+//! Documents are compared as parsed values, so formatting, comments, key
+//! order within a table and the choice between inline and standard tables
+//! make no difference. Tables are compared key by key and arrays index by
+//! index; any other difference is reported as a change of the whole value.
+
+use std::fmt::Write as _;
+use toml::Value as TomlValue;
+
+use crate::document::load_toml;
+use crate::error::Result;
+use crate::flatten::inline_value;
+use crate::path::{format_path, PathSegment};
+
+/// Kind of a difference between two documents
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    /// Only present in the new document
+    Added,
+    /// Only present in the old document
+    Removed,
+    /// Present in both with different values
+    Changed,
+}
+
+impl DiffKind {
+    /// Lowercase name used in JSON output
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Added => "added",
+            Self::Removed => "removed",
+            Self::Changed => "changed",
+        }
+    }
+}
+
+/// One difference, located by a field path
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffEntry {
+    /// Field path in the syntax of [`crate::path`]
+    pub path: String,
+    /// Kind of difference
+    pub kind: DiffKind,
+    /// Value in the old document, unless added
+    pub old: Option<TomlValue>,
+    /// Value in the new document, unless removed
+    pub new: Option<TomlValue>,
+}
+
+/// Compare two TOML files
+pub fn diff_files(old_path: &str, new_path: &str) -> Result<Vec<DiffEntry>> {
+    let old = load_toml(old_path)?;
+    let new = load_toml(new_path)?;
+    Ok(diff_values(&old, &new))
+}
+
+/// Compare two values, listing differences in document order
+///
+/// Keys of the old document come first, followed by keys only present in
+/// the new one.
+pub fn diff_values(old: &TomlValue, new: &TomlValue) -> Vec<DiffEntry> {
+    let mut entries = Vec::new();
+    diff_into(&mut Vec::new(), old, new, &mut entries);
+    entries
+}
+
+fn diff_into(
+    segments: &mut Vec<PathSegment>,
+    old: &TomlValue,
+    new: &TomlValue,
+    entries: &mut Vec<DiffEntry>,
+) {
+    match (old, new) {
+        (TomlValue::Table(old_table), TomlValue::Table(new_table)) => {
+            for (key, old_child) in old_table {
+                segments.push(PathSegment::Key(key.clone()));
+                match new_table.get(key) {
+                    Some(new_child) => diff_into(segments, old_child, new_child, entries),
+                    None => entries.push(entry(segments, DiffKind::Removed, Some(old_child), None)),
+                }
+                segments.pop();
+            }
+            for (key, new_child) in new_table {
+                if !old_table.contains_key(key) {
+                    segments.push(PathSegment::Key(key.clone()));
+                    entries.push(entry(segments, DiffKind::Added, None, Some(new_child)));
+                    segments.pop();
+                }
+            }
+        }
+        (TomlValue::Array(old_array), TomlValue::Array(new_array)) => {
+            for index in 0..old_array.len().max(new_array.len()) {
+                segments.push(PathSegment::Index(index));
+                match (old_array.get(index), new_array.get(index)) {
+                    (Some(old_elem), Some(new_elem)) => {
+                        diff_into(segments, old_elem, new_elem, entries)
+                    }
+                    (Some(old_elem), None) => {
+                        entries.push(entry(segments, DiffKind::Removed, Some(old_elem), None))
+                    }
+                    (None, new_elem) => {
+                        entries.push(entry(segments, DiffKind::Added, None, new_elem))
+                    }
+                }
+                segments.pop();
+            }
+        }
+        // `nan` never equals itself, but the two files say the same thing
+        (TomlValue::Float(a), TomlValue::Float(b)) if a.is_nan() && b.is_nan() => {}
+        _ if old != new => entries.push(entry(segments, DiffKind::Changed, Some(old), Some(new))),
+        _ => {}
+    }
+}
+
+fn entry(
+    segments: &[PathSegment],
+    kind: DiffKind,
+    old: Option<&TomlValue>,
+    new: Option<&TomlValue>,
+) -> DiffEntry {
+    DiffEntry {
+        path: format_path(segments),
+        kind,
+        old: old.cloned(),
+        new: new.cloned(),
+    }
+}
+
+/// Render differences as `+ path = new`, `- path = old` and `~ path = old -> new` lines
+pub fn render_diff(entries: &[DiffEntry]) -> String {
+    let mut out = String::new();
+    for entry in entries {
+        match (&entry.old, &entry.new) {
+            (Some(old), Some(new)) => writeln!(
+                out,
+                "~ {} = {} -> {}",
+                entry.path,
+                inline_value(old),
+                inline_value(new)
+            ),
+            (None, Some(new)) => writeln!(out, "+ {} = {}", entry.path, inline_value(new)),
+            (Some(old), None) => writeln!(out, "- {} = {}", entry.path, inline_value(old)),
+            (None, None) => Ok(()),
+        }
+        .expect("writing to a String cannot fail");
+    }
+    out
+}
+
+/// Convert differences to a JSON array of `{"kind", "path", "old", "new"}` objects
+///
+/// `old` is omitted for added paths and `new` for removed ones.
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub fn diff_to_json(entries: &[DiffEntry]) -> Result<serde_json::Value> {
+    use crate::get::utils::to_json_value;
+    use serde_json::Value as JsonValue;
+
+    let mut array = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let mut object = serde_json::Map::new();
+        object.insert("kind".to_string(), entry.kind.as_str().into());
+        object.insert("path".to_string(), entry.path.clone().into());
+        if let Some(old) = &entry.old {
+            object.insert("old".to_string(), to_json_value(old)?);
+        }
+        if let Some(new) = &entry.new {
+            object.insert("new".to_string(), to_json_value(new)?);
+        }
+        array.push(JsonValue::Object(object));
+    }
+    Ok(JsonValue::Array(array))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_ignores_formatting() {
+        let old: TomlValue =
+            toml::from_str("[package]\nname = \"demo\"\nversion = \"1.0\"\n# comment\n").unwrap();
+        let new: TomlValue =
+            toml::from_str("package = { version = '1.0', name = \"demo\" }").unwrap();
+        assert!(diff_values(&old, &new).is_empty());
+
+        let old: TomlValue = toml::from_str(
+            "a = nan
+b = [nan, 1.0]",
+        )
+        .unwrap();
+        let new: TomlValue = toml::from_str(
+            "a = nan
+b = [-nan, 1.0]",
+        )
+        .unwrap();
+        assert!(diff_values(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn test_diff_values() {
+        let old: TomlValue = toml::from_str(
+            "[package]\nname = \"demo\"\nversion = \"1.0\"\nauthors = [\"Ann\", \"Bob\"]\nreadme = \"README.md\"",
+        )
+        .unwrap();
+        let new: TomlValue = toml::from_str(
+            "[package]\nname = \"demo\"\nversion = \"1.1\"\nauthors = [\"Ann\"]\nlicense = \"MIT\"",
+        )
+        .unwrap();
+
+        let entries = diff_values(&old, &new);
+        assert_eq!(
+            render_diff(&entries),
+            "~ package.version = \"1.0\" -> \"1.1\"\n\
+             - package.authors[1] = \"Bob\"\n\
+             - package.readme = \"README.md\"\n\
+             + package.license = \"MIT\"\n"
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_diff_to_json() {
+        let old: TomlValue = toml::from_str("a = 1").unwrap();
+        let new: TomlValue = toml::from_str("a = \"1\"\nb = true").unwrap();
+        assert_eq!(
+            diff_to_json(&diff_values(&old, &new)).unwrap().to_string(),
+            r#"[{"kind":"changed","path":"a","old":1,"new":"1"},{"kind":"added","path":"b","new":true}]"#
+        );
+    }
+}
@@ -0,0 +1,10 @@
+//! Semantic comparison of two TOML documents
+
+pub mod core;
+#[cfg(feature = "cli")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cli")))]
+pub mod xcli;
+
+pub use core::*;
+#[cfg(feature = "cli")]
+pub use xcli::*;
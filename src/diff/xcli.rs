@@ -0,0 +1,62 @@
+//! CLI definitions and handling for diff command
+
+use clap::{Arg, ArgMatches, Command};
+
+use super::core::*;
+use crate::error::Result;
+
+/// Define the diff command CLI structure
+pub fn diff_command() -> Command {
+    Command::new("diff")
+        .about("Compare two TOML files by value (exit status 5 if they differ)")
+        .arg(
+            Arg::new("old")
+                .value_name("OLD")
+                .help("Old TOML file")
+                .required(true),
+        )
+        .arg(
+            Arg::new("new")
+                .value_name("NEW")
+                .help("New TOML file")
+                .required(true),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("FORMAT")
+                .help("Output format (text, json)")
+                .value_parser(["text", "json"])
+                .default_value("text"),
+        )
+        .arg(
+            Arg::new("quiet")
+                .short('q')
+                .long("quiet")
+                .help("Print nothing; only set the exit status")
+                .action(clap::ArgAction::SetTrue),
+        )
+}
+
+/// Handle diff command logic
+///
+/// Returns whether the files differ; the caller turns `Ok(true)` into exit status 5.
+pub fn handle_diff_command(matches: &ArgMatches) -> Result<bool> {
+    let entries = diff_files(
+        matches.get_one::<String>("old").unwrap(),
+        matches.get_one::<String>("new").unwrap(),
+    )?;
+
+    if !matches.get_flag("quiet") {
+        match matches.get_one::<String>("output").map(String::as_str) {
+            Some("json") => println!(
+                "{}",
+                serde_json::to_string_pretty(&diff_to_json(&entries)?)?
+            ),
+            _ => print!("{}", render_diff(&entries)),
+        }
+    }
+
+    Ok(!entries.is_empty())
+}
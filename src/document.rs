@@ -0,0 +1,90 @@
+//! Reading, parsing and writing TOML documents
+
+use std::fs;
+use std::io::Read;
+use toml::Value as TomlValue;
+#[cfg(feature = "fmt")]
+use toml_edit::DocumentMut;
+
+use crate::error::{Result, TomlExtractError};
+
+/// Read a file to a string, attaching the path to any I/O error
+pub fn read_file(file_path: &str) -> Result<String> {
+    fs::read_to_string(file_path).map_err(|e| TomlExtractError::io(file_path, e))
+}
+
+/// Read a file, or standard input when `file_path` is `-`
+pub fn read_input(file_path: &str) -> Result<String> {
+    if file_path != "-" {
+        return read_file(file_path);
+    }
+    let mut content = String::new();
+    std::io::stdin()
+        .read_to_string(&mut content)
+        .map_err(|e| TomlExtractError::io("<stdin>", e))?;
+    Ok(content)
+}
+
+/// Write a string to a file, attaching the path to any I/O error
+pub fn write_file(file_path: &str, content: &str) -> Result<()> {
+    fs::write(file_path, content).map_err(|e| TomlExtractError::io(file_path, e))
+}
+
+/// Parse TOML source text; `file_path` is only used for error reporting
+pub fn parse_toml(content: &str, file_path: &str) -> Result<TomlValue> {
+    toml::from_str(content).map_err(|e| TomlExtractError::parse(file_path, content, &e))
+}
+
+/// Parse JSON source text; `file_path` is only used for error reporting
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub fn parse_json<T: serde::de::DeserializeOwned>(content: &str, file_path: &str) -> Result<T> {
+    serde_json::from_str(content).map_err(|e| TomlExtractError::parse_json(file_path, content, e))
+}
+
+/// Parse YAML source text; `file_path` is only used for error reporting
+#[cfg(feature = "yaml")]
+#[cfg_attr(docsrs, doc(cfg(feature = "yaml")))]
+pub fn parse_yaml<T: serde::de::DeserializeOwned>(content: &str, file_path: &str) -> Result<T> {
+    serde_yaml::from_str(content).map_err(|e| TomlExtractError::parse_yaml(file_path, content, e))
+}
+
+/// Parse TOML source text into an editable document that keeps comments and layout
+#[cfg(feature = "fmt")]
+#[cfg_attr(docsrs, doc(cfg(feature = "fmt")))]
+pub fn parse_toml_edit(content: &str, file_path: &str) -> Result<DocumentMut> {
+    content
+        .parse()
+        .map_err(|e| TomlExtractError::parse_edit(file_path, content, &e))
+}
+
+/// Read and parse a TOML file
+pub fn load_toml(file_path: &str) -> Result<TomlValue> {
+    let content = read_file(file_path)?;
+    parse_toml(&content, file_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_toml_missing_file() {
+        let err = load_toml("definitely/not/here.toml").unwrap_err();
+        assert!(matches!(err, TomlExtractError::FileNotFound(_)));
+    }
+
+    #[test]
+    fn test_parse_toml_error_has_span() {
+        let err = parse_toml("[package]\nname = \n", "broken.toml").unwrap_err();
+        match err {
+            TomlExtractError::InvalidToml { file, span, .. } => {
+                assert_eq!(file, "broken.toml");
+                let span = span.unwrap();
+                assert_eq!(span.line, 2);
+                assert_eq!(span.line_text, "name = ");
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+}
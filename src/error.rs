@@ -1,5 +1,9 @@
+use std::ops::Range;
 use thiserror::Error;
 
+/// Result type used throughout the library.
+pub type Result<T, E = TomlExtractError> = std::result::Result<T, E>;
+
 /// Error types for TOML extraction and manipulation operations.
 #[derive(Error, Debug)]
 pub enum TomlExtractError {
@@ -7,21 +11,68 @@ pub enum TomlExtractError {
     #[error("File not found: {0}")]
     FileNotFound(String),
 
+    /// An I/O error occurred while reading/writing the file.
+    #[error("IO error on {path}: {source}")]
+    Io {
+        /// Path of the file being read or written.
+        path: String,
+        /// Underlying I/O error.
+        #[source]
+        source: std::io::Error,
+    },
+
     /// The TOML file contains invalid syntax.
-    #[error("Invalid TOML syntax in {file}: {error}")]
+    #[error("Invalid TOML syntax in {}: {}", location(.file, .span), .message.replace('\n', ", "))]
     InvalidToml {
         /// Path to the invalid TOML file.
         file: String,
         /// Description of the syntax error.
-        error: String,
+        message: String,
+        /// Location of the offending input, if known.
+        span: Option<SourceSpan>,
+    },
+
+    /// A JSON or YAML input contains invalid syntax.
+    #[error("Invalid {format} syntax in {}: {}", location(.file, .span), .message)]
+    InvalidInput {
+        /// Path to the invalid input.
+        file: String,
+        /// Name of the input format (`JSON`, `YAML`).
+        format: &'static str,
+        /// Description of the syntax error.
+        message: String,
+        /// Location of the offending input, if known.
+        span: Option<SourceSpan>,
     },
 
     /// The specified field path does not exist in the TOML file.
-    #[error("Field not found: {0}")]
-    FieldNotFound(String),
+    #[error("Field not found: {path}{}", suggestion_hint(.suggestion))]
+    FieldNotFound {
+        /// The full field path that was requested.
+        path: String,
+        /// The path segment that could not be resolved.
+        missing: String,
+        /// The deepest prefix of `path` that exists (empty for the document root).
+        found: String,
+        /// Keys available in the table at `found`.
+        available: Vec<String>,
+        /// The closest available key, as a full path, if one is similar enough.
+        suggestion: Option<String>,
+    },
+
+    /// Several requested fields do not exist in the TOML file.
+    #[error("Fields not found: {}", .0.join(", "))]
+    MissingFields(Vec<String>),
+
+    /// A `sort` pattern matched no tables or arrays.
+    #[error("Pattern matched no tables: {0}")]
+    NoMatch(String),
 
     /// An array index is out of bounds.
-    #[error("Array index out of bounds: {path}[{index}], array length: {length}")]
+    #[error(
+        "Array index out of bounds: {path}[{index}], array length: {length}{}",
+        index_range_hint(*.length)
+    )]
     ArrayIndexOutOfBounds {
         /// Path to the array in the TOML file.
         path: String,
@@ -39,14 +90,33 @@ pub enum TomlExtractError {
     #[error("Invalid array index: {0}")]
     InvalidArrayIndex(String),
 
-    /// An I/O error occurred while reading/writing the file.
-    #[error("IO error: {0}")]
-    IoError(#[from] std::io::Error),
+    /// The value at a path has a different type than the operation expects.
+    #[error("Type mismatch at {}: expected {expected}, found {found}", path_label(.path))]
+    TypeMismatch {
+        /// Path to the offending value (empty for the document root).
+        path: String,
+        /// The type the operation expected.
+        expected: String,
+        /// The type actually found.
+        found: String,
+    },
 
     /// An error occurred during JSON serialization/deserialization.
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
     #[error("JSON serialization error: {0}")]
     JsonError(#[from] serde_json::Error),
 
+    /// An error occurred during YAML serialization/deserialization.
+    #[cfg(feature = "yaml")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "yaml")))]
+    #[error("YAML serialization error: {0}")]
+    YamlError(#[from] serde_yaml::Error),
+
+    /// An error occurred while serializing a TOML document.
+    #[error("TOML serialization error: {0}")]
+    TomlSerialize(#[from] toml::ser::Error),
+
     /// The specified value is not a TOML table (dictionary).
     #[error("Not a table: {0}")]
     NotATable(String),
@@ -55,7 +125,415 @@ pub enum TomlExtractError {
     #[error("Invalid field path: {0}")]
     InvalidFieldPath(String),
 
+    /// A `--format` template is malformed or uses an unknown filter.
+    #[error("Invalid template: {0}")]
+    InvalidTemplate(String),
+
+    /// An `assert` rule is malformed.
+    #[error("Invalid rule `{}`{}: {message}", .span.line_text, rule_location(.file, .span))]
+    InvalidRule {
+        /// Policy file holding the rule; empty for a rule given directly.
+        file: String,
+        /// What is wrong with the rule.
+        message: String,
+        /// The rule's line, within `file` if it came from one.
+        span: SourceSpan,
+    },
+
     /// The value type is invalid for the requested operation.
     #[error("Invalid value type: {0}")]
     InvalidValueType(String),
+
+    /// An argument cannot be used as given: an unknown option value, an
+    /// unsupported combination of options, a malformed schema or patch, or a
+    /// feature that was not compiled in.
+    #[error("{0}")]
+    InvalidArgument(String),
+
+    /// A value differs from the one a check expected.
+    #[error("Test failed at {path}: expected {expected}, found {found}")]
+    TestFailed {
+        /// Path to the checked value.
+        path: String,
+        /// The expected value.
+        expected: String,
+        /// The value actually found.
+        found: String,
+    },
+
+    /// An operation of a patch failed, so none of the patch was applied.
+    #[error("Patch operation {index} ({op}) failed: {source}")]
+    PatchFailed {
+        /// Zero-based position of the operation in the patch.
+        index: usize,
+        /// Name of the operation (`add`, `remove`, ...).
+        op: String,
+        /// Why the operation failed.
+        #[source]
+        source: Box<TomlExtractError>,
+    },
+}
+
+impl TomlExtractError {
+    /// Build an I/O error for `path`, mapping "not found" to [`TomlExtractError::FileNotFound`].
+    pub fn io(path: &str, source: std::io::Error) -> Self {
+        if source.kind() == std::io::ErrorKind::NotFound {
+            Self::FileNotFound(path.to_string())
+        } else {
+            Self::Io {
+                path: path.to_string(),
+                source,
+            }
+        }
+    }
+
+    /// Build a parse error for `file` from a `toml` deserialization error over `source`.
+    pub fn parse(file: &str, source: &str, error: &toml::de::Error) -> Self {
+        Self::InvalidToml {
+            file: file.to_string(),
+            message: error.message().trim_end().to_string(),
+            span: error
+                .span()
+                .map(|range| SourceSpan::from_range(source, range)),
+        }
+    }
+
+    /// Build a parse error for `file` from a `toml_edit` error over `source`.
+    #[cfg(feature = "fmt")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "fmt")))]
+    pub fn parse_edit(file: &str, source: &str, error: &toml_edit::TomlError) -> Self {
+        Self::InvalidToml {
+            file: file.to_string(),
+            message: error.message().trim_end().to_string(),
+            span: error
+                .span()
+                .map(|range| SourceSpan::from_range(source, range)),
+        }
+    }
+
+    /// Build a parse error for `file` from a `serde_json` error over `source`.
+    ///
+    /// Errors other than syntax errors stay [`TomlExtractError::JsonError`].
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn parse_json(file: &str, source: &str, error: serde_json::Error) -> Self {
+        if !(error.is_syntax() || error.is_eof()) {
+            return Self::JsonError(error);
+        }
+        let (line, column) = (error.line(), error.column());
+        let message = error.to_string();
+        Self::InvalidInput {
+            file: file.to_string(),
+            format: "JSON",
+            message: message
+                .trim_end_matches(&format!(" at line {} column {}", line, column))
+                .to_string(),
+            span: (line > 0).then(|| SourceSpan::from_line_column(source, line, column)),
+        }
+    }
+
+    /// Build a parse error for `file` from a `serde_yaml` error over `source`.
+    #[cfg(feature = "yaml")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "yaml")))]
+    pub fn parse_yaml(file: &str, source: &str, error: serde_yaml::Error) -> Self {
+        let location = error.location();
+        let mut message = error.to_string();
+        if let Some(location) = &location {
+            let suffix = format!(" at line {} column {}", location.line(), location.column());
+            message = message.trim_end_matches(&suffix).to_string();
+        }
+        Self::InvalidInput {
+            file: file.to_string(),
+            format: "YAML",
+            message,
+            span: location
+                .map(|location| SourceSpan::from_range(source, location.index()..location.index())),
+        }
+    }
+
+    /// Whether the error means the requested field does not exist
+    ///
+    /// True for missing keys and out-of-bounds array indices, the cases where a
+    /// default value may stand in for the field.
+    pub fn is_not_found(&self) -> bool {
+        matches!(
+            self,
+            Self::FieldNotFound { .. }
+                | Self::MissingFields(_)
+                | Self::ArrayIndexOutOfBounds { .. }
+        )
+    }
+
+    /// Process exit code for this error, as used by the CLI.
+    ///
+    /// | Code | Meaning |
+    /// | ---- | ------- |
+    /// | 0 | Success (the field was found / written) |
+    /// | 1 | Field not found (missing key, index out of bounds, invalid path or template, unmatched pattern), failed test |
+    /// | 2 | TOML, JSON or YAML parse error, malformed `assert` rule |
+    /// | 3 | I/O error (including a missing file) |
+    /// | 4 | Type mismatch (wrong value type, or a value that cannot be converted) |
+    /// | 64 | Invalid argument (unknown option value, unsupported combination, malformed schema or patch) |
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::FieldNotFound { .. }
+            | Self::MissingFields(_)
+            | Self::NoMatch(_)
+            | Self::ArrayIndexOutOfBounds { .. }
+            | Self::InvalidArrayIndex(_)
+            | Self::InvalidFieldPath(_)
+            | Self::InvalidTemplate(_)
+            | Self::TestFailed { .. } => 1,
+            Self::InvalidToml { .. } | Self::InvalidInput { .. } | Self::InvalidRule { .. } => 2,
+            Self::FileNotFound(_) | Self::Io { .. } => 3,
+            Self::TypeMismatch { .. }
+            | Self::NotAnArray(_)
+            | Self::NotATable(_)
+            | Self::InvalidValueType(_)
+            | Self::TomlSerialize(_) => 4,
+            Self::InvalidArgument(_) => 64,
+            Self::PatchFailed { source, .. } => source.exit_code(),
+            #[cfg(feature = "json")]
+            Self::JsonError(_) => 4,
+            #[cfg(feature = "yaml")]
+            Self::YamlError(_) => 4,
+        }
+    }
+
+    /// Render the error for a terminal.
+    ///
+    /// Parse errors with a known location are shown compiler-style, with the
+    /// offending line and a caret under the span; other errors fall back to
+    /// their `Display` text.
+    pub fn diagnostic(&self) -> String {
+        match self {
+            Self::InvalidToml {
+                file,
+                message,
+                span: Some(span),
+            } => span.render(file, message),
+            Self::InvalidInput {
+                file,
+                format,
+                message,
+                span: Some(span),
+            } => span.render(file, &format!("invalid {}: {}", format, message)),
+            Self::InvalidRule {
+                file,
+                message,
+                span,
+            } if !file.is_empty() => span.render(file, &format!("invalid rule: {}", message)),
+            Self::FieldNotFound {
+                missing,
+                found,
+                available,
+                ..
+            } => {
+                let mut out = format!("error: {}", self);
+                let parent = if found.is_empty() {
+                    "the document root".to_string()
+                } else {
+                    format!("`{}`", found)
+                };
+                out.push_str(&format!("\n  = note: {} has no key `{}`", parent, missing));
+                if !available.is_empty() {
+                    out.push_str(&format!(
+                        "\n  = note: available keys: {}",
+                        available.join(", ")
+                    ));
+                }
+                out
+            }
+            _ => format!("error: {}", self),
+        }
+    }
+}
+
+/// Location of a syntax error within a TOML source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceSpan {
+    /// 1-based line number of the start of the span.
+    pub line: usize,
+    /// 1-based column (in characters) of the start of the span.
+    pub column: usize,
+    /// Byte range of the span within the source.
+    pub range: Range<usize>,
+    /// Full text of the line containing the start of the span.
+    pub line_text: String,
+}
+
+impl SourceSpan {
+    /// Compute line, column and line text for a byte range of `source`.
+    pub fn from_range(source: &str, range: Range<usize>) -> Self {
+        let start = floor_char_boundary(source, range.start.min(source.len()));
+        let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |i| start + i);
+        let line_text = source[line_start..line_end].trim_end_matches('\r');
+
+        Self {
+            line: source[..start].matches('\n').count() + 1,
+            column: source[line_start..start].chars().count() + 1,
+            range,
+            line_text: line_text.to_string(),
+        }
+    }
+
+    /// Compute the span of a 1-based line and column of `source`.
+    pub fn from_line_column(source: &str, line: usize, column: usize) -> Self {
+        let line_start: usize = source
+            .split_inclusive('\n')
+            .take(line.saturating_sub(1))
+            .map(str::len)
+            .sum();
+        let start = (line_start + column.saturating_sub(1)).min(source.len());
+        Self::from_range(source, start..start)
+    }
+
+    /// Render a compiler-style report of `message` at this span in `file`.
+    pub fn render(&self, file: &str, message: &str) -> String {
+        let gutter = self.line.to_string().len();
+        let pad = " ".repeat(gutter);
+        let indent: String = self
+            .line_text
+            .chars()
+            .take(self.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let remaining = self.line_text.chars().count() + 1 - self.column;
+        let width = self.range.len().clamp(1, remaining.max(1));
+        let mut lines = message.lines();
+
+        let mut out = format!(
+            "error: {headline}\n{pad}--> {file}:{line}:{column}\n{pad} |\n{line} | {text}\n{pad} | {indent}{carets}",
+            headline = lines.next().unwrap_or_default(),
+            line = self.line,
+            column = self.column,
+            text = self.line_text,
+            carets = "^".repeat(width),
+        );
+        for note in lines {
+            out.push_str(&format!("\n{pad} = {note}"));
+        }
+        out
+    }
+}
+
+fn suggestion_hint(suggestion: &Option<String>) -> String {
+    match suggestion {
+        Some(path) => format!(" (did you mean `{}`?)", path),
+        None => String::new(),
+    }
+}
+
+fn path_label(path: &str) -> &str {
+    match path {
+        "" => "<root>",
+        path => path,
+    }
+}
+
+fn index_range_hint(length: usize) -> String {
+    match length {
+        0 => ", the array is empty".to_string(),
+        n => format!(", valid indices: 0..={}", n - 1),
+    }
+}
+
+fn location(file: &str, span: &Option<SourceSpan>) -> String {
+    match span {
+        Some(span) => format!("{}:{}:{}", file, span.line, span.column),
+        None => file.to_string(),
+    }
+}
+
+fn rule_location(file: &str, span: &SourceSpan) -> String {
+    match file {
+        "" => String::new(),
+        file => format!(" at {}:{}", file, span.line),
+    }
+}
+
+fn floor_char_boundary(s: &str, mut index: usize) -> usize {
+    while !s.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_span_from_range() {
+        let source = "[package]\nname = \"x\"\nversion = = 1\n";
+        let start = source.find("= 1").unwrap();
+        let span = SourceSpan::from_range(source, start..start + 1);
+        assert_eq!(span.line, 3);
+        assert_eq!(span.column, 11);
+        assert_eq!(span.line_text, "version = = 1");
+    }
+
+    #[test]
+    fn test_exit_codes() {
+        assert_eq!(TomlExtractError::FileNotFound("x".into()).exit_code(), 3);
+        assert_eq!(TomlExtractError::NotAnArray("x".into()).exit_code(), 4);
+        assert_eq!(
+            TomlExtractError::ArrayIndexOutOfBounds {
+                path: "a".into(),
+                index: 1,
+                length: 0,
+            }
+            .exit_code(),
+            1
+        );
+        let parse = toml::from_str::<toml::Value>("a = ").unwrap_err();
+        assert_eq!(TomlExtractError::parse("x", "a = ", &parse).exit_code(), 2);
+        assert_eq!(
+            TomlExtractError::InvalidArgument("x".into()).exit_code(),
+            64
+        );
+    }
+
+    #[test]
+    fn test_type_mismatch_at_root() {
+        let error = TomlExtractError::TypeMismatch {
+            path: String::new(),
+            expected: "table".into(),
+            found: "array".into(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Type mismatch at <root>: expected table, found array"
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_parse_error() {
+        let source = "{\n  \"a\": ,\n}";
+        let error = serde_json::from_str::<serde_json::Value>(source).unwrap_err();
+        match TomlExtractError::parse_json("x.json", source, error) {
+            error @ TomlExtractError::InvalidInput { .. } => {
+                assert_eq!(error.exit_code(), 2);
+                assert_eq!(
+                    error.to_string(),
+                    "Invalid JSON syntax in x.json:2:8: expected value"
+                );
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_render_points_at_column() {
+        let span = SourceSpan::from_range("a = 1\nb = ?\n", 10..11);
+        let rendered = span.render("x.toml", "invalid string");
+        assert_eq!(
+            rendered,
+            "error: invalid string\n --> x.toml:2:5\n  |\n2 | b = ?\n  |     ^"
+        );
+    }
 }
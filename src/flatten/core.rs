@@ -0,0 +1,249 @@
+//! Core functionality for paths and unflatten commands
+//!
+//! A flattened document is one `path = value` line per leaf, in document
+//! order. Paths use the syntax of [`crate::path`] and values are TOML inline
+//! values kept on a single line, so the output can be grepped, sorted or
+//! diffed and then turned back into a document with [`unflatten_str`].
+
+use std::fmt::Write as _;
+use toml::{Table, Value as TomlValue};
+
+use crate::document::{load_toml, read_file};
+use crate::error::{Result, SourceSpan, TomlExtractError};
+use crate::get::utils::get_nested_value;
+use crate::path::{format_path, parse_path, set_value_at, split_unquoted, PathSegment};
+
+/// List every leaf below `field_path` (the whole document if empty) with its full path
+///
+/// Empty tables and arrays are leaves too, so that nothing is lost when the
+/// list is unflattened again.
+pub fn flatten_file(file_path: &str, field_path: &str) -> Result<Vec<(String, TomlValue)>> {
+    let value = load_toml(file_path)?;
+    let mut segments = parse_path(field_path)?;
+    let mut leaves = Vec::new();
+    flatten_into(
+        &mut segments,
+        get_nested_value(&value, field_path)?,
+        &mut leaves,
+    );
+    Ok(leaves)
+}
+
+/// List every leaf of a value, with paths relative to it
+pub fn flatten_document(value: &TomlValue) -> Vec<(String, TomlValue)> {
+    let mut leaves = Vec::new();
+    flatten_into(&mut Vec::new(), value, &mut leaves);
+    leaves
+}
+
+fn flatten_into(
+    segments: &mut Vec<PathSegment>,
+    value: &TomlValue,
+    leaves: &mut Vec<(String, TomlValue)>,
+) {
+    match value {
+        TomlValue::Table(table) if !table.is_empty() => {
+            for (key, child) in table {
+                segments.push(PathSegment::Key(key.clone()));
+                flatten_into(segments, child, leaves);
+                segments.pop();
+            }
+        }
+        TomlValue::Array(array) if !array.is_empty() => {
+            for (index, child) in array.iter().enumerate() {
+                segments.push(PathSegment::Index(index));
+                flatten_into(segments, child, leaves);
+                segments.pop();
+            }
+        }
+        _ => leaves.push((format_path(segments), value.clone())),
+    }
+}
+
+/// Render leaves as `path = value` lines
+pub fn render_flat(leaves: &[(String, TomlValue)]) -> String {
+    let mut out = String::new();
+    for (path, value) in leaves {
+        writeln!(out, "{} = {}", path, inline_value(value))
+            .expect("writing to a String cannot fail");
+    }
+    out
+}
+
+/// Format a value as a single-line TOML inline value
+///
+/// Unlike `Display`, strings containing newlines stay on one line as escaped
+/// basic strings.
+pub fn inline_value(value: &TomlValue) -> String {
+    match value {
+        TomlValue::String(s) => {
+            let mut out = String::from("\"");
+            for c in s.chars() {
+                match c {
+                    '"' => out.push_str("\\\""),
+                    '\\' => out.push_str("\\\\"),
+                    '\n' => out.push_str("\\n"),
+                    '\r' => out.push_str("\\r"),
+                    '\t' => out.push_str("\\t"),
+                    c if c.is_control() => {
+                        write!(out, "\\u{:04X}", c as u32).expect("writing to a String cannot fail")
+                    }
+                    c => out.push(c),
+                }
+            }
+            out.push('"');
+            out
+        }
+        TomlValue::Array(array) => format!(
+            "[{}]",
+            array
+                .iter()
+                .map(inline_value)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        TomlValue::Table(table) if table.is_empty() => "{}".to_string(),
+        TomlValue::Table(table) => format!(
+            "{{ {} }}",
+            table
+                .iter()
+                .map(|(key, child)| format!(
+                    "{} = {}",
+                    crate::path::format_key(key),
+                    inline_value(child)
+                ))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        other => other.to_string(),
+    }
+}
+
+/// Build a document from `path = value` lines read from a file
+pub fn unflatten_file(file_path: &str) -> Result<TomlValue> {
+    let content = read_file(file_path)?;
+    unflatten_str(&content, file_path)
+}
+
+/// Build a document from `path = value` lines
+///
+/// Blank lines and lines starting with `#` are skipped. Later lines win over
+/// earlier ones, and array elements may appear in any order; gaps are
+/// filled with empty strings. `file_path` is only used for error reporting.
+pub fn unflatten_str(content: &str, file_path: &str) -> Result<TomlValue> {
+    let mut document = TomlValue::Table(Table::new());
+    let mut offset = 0;
+
+    for line in content.split_inclusive('\n') {
+        let range = offset..offset + line.trim_end().len();
+        offset += line.len();
+
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = |message: String| TomlExtractError::InvalidToml {
+            file: file_path.to_string(),
+            message,
+            span: Some(SourceSpan::from_range(content, range.clone())),
+        };
+
+        let (path, raw_value) =
+            split_assignment(line).ok_or_else(|| invalid("expected `path = value`".to_string()))?;
+        let segments = parse_path(path).map_err(|e| match e {
+            TomlExtractError::InvalidFieldPath(message)
+            | TomlExtractError::InvalidArrayIndex(message) => invalid(message),
+            other => other,
+        })?;
+        let value = toml::from_str::<Table>(&format!("value = {}", raw_value))
+            .ok()
+            .and_then(|mut table| table.remove("value"))
+            .ok_or_else(|| invalid(format!("invalid TOML value `{}`", raw_value)))?;
+
+        if segments.is_empty() {
+            return Err(invalid("empty path".to_string()));
+        }
+        set_value_at(&mut document, &segments, value, true)?;
+    }
+
+    Ok(document)
+}
+
+/// Split `path = value` at the first `=` outside a quoted key
+fn split_assignment(line: &str) -> Option<(&str, &str)> {
+    split_unquoted(line, '=').map(|(path, value)| (path.trim(), value.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"
+title = """multi
+line"""
+
+[package]
+name = "demo"
+authors = ["Ann", "Bob"]
+
+[target."cfg(unix)".dependencies]
+libc = "0.2"
+
+[[bin]]
+name = "a"
+path = "src/a.rs"
+
+[features]
+default = []
+"#;
+
+    #[test]
+    fn test_flatten_document() {
+        let value: TomlValue = toml::from_str(SAMPLE).unwrap();
+        let flat = render_flat(&flatten_document(&value));
+        assert_eq!(
+            flat,
+            "title = \"multi\\nline\"\n\
+             package.name = \"demo\"\n\
+             package.authors[0] = \"Ann\"\n\
+             package.authors[1] = \"Bob\"\n\
+             target.\"cfg(unix)\".dependencies.libc = \"0.2\"\n\
+             bin[0].name = \"a\"\n\
+             bin[0].path = \"src/a.rs\"\n\
+             features.default = []\n"
+        );
+    }
+
+    #[test]
+    fn test_unflatten_round_trip() {
+        let value: TomlValue = toml::from_str(SAMPLE).unwrap();
+        let mut lines: Vec<String> = render_flat(&flatten_document(&value))
+            .lines()
+            .map(str::to_string)
+            .collect();
+        lines.sort();
+        lines.insert(0, "# sorted".to_string());
+
+        assert_eq!(unflatten_str(&lines.join("\n"), "flat.txt").unwrap(), value);
+    }
+
+    #[test]
+    fn test_unflatten_errors() {
+        let err = unflatten_str("a = 1\nb = nope\n", "flat.txt").unwrap_err();
+        match err {
+            TomlExtractError::InvalidToml { span, .. } => assert_eq!(span.unwrap().line, 2),
+            other => panic!("unexpected error: {other:?}"),
+        }
+        assert!(unflatten_str("just words", "flat.txt").is_err());
+
+        let err = unflatten_str("a = 1\n\nb..c = 2\n", "flat.txt").unwrap_err();
+        match &err {
+            TomlExtractError::InvalidToml { span, message, .. } => {
+                assert_eq!(span.as_ref().unwrap().line, 3);
+                assert_eq!(message, "Empty path segment in path 'b..c'");
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+        assert!(err.diagnostic().contains("b..c = 2"));
+    }
+}
@@ -0,0 +1,74 @@
+//! CLI definitions and handling for paths and unflatten commands
+
+use clap::{Arg, ArgMatches, Command};
+
+use super::core::*;
+use crate::document::{read_input, write_file};
+use crate::error::Result;
+
+/// Define the paths command CLI structure
+pub fn paths_command() -> Command {
+    Command::new("paths")
+        .about("Print every leaf as `path = value`, one per line (below PATH if given)")
+        .arg(
+            Arg::new("path")
+                .value_name("PATH")
+                .help("Only list leaves below this field path"),
+        )
+        .arg(
+            Arg::new("file")
+                .short('f')
+                .long("file")
+                .value_name("FILE")
+                .help("TOML file path")
+                .default_value("Cargo.toml"),
+        )
+}
+
+/// Define the unflatten command CLI structure
+pub fn unflatten_command() -> Command {
+    Command::new("unflatten")
+        .about("Build a TOML document from `path = value` lines, as printed by paths")
+        .arg(
+            Arg::new("file")
+                .short('f')
+                .long("file")
+                .value_name("FILE")
+                .help("File with `path = value` lines (- for stdin)")
+                .default_value("-"),
+        )
+        .arg(
+            Arg::new("output-file")
+                .short('o')
+                .long("output-file")
+                .value_name("FILE")
+                .help("Write the document to FILE instead of stdout"),
+        )
+}
+
+/// Handle paths command logic
+pub fn handle_paths_command(matches: &ArgMatches) -> Result<()> {
+    let file_path = matches.get_one::<String>("file").unwrap();
+    let field_path = matches
+        .get_one::<String>("path")
+        .map(String::as_str)
+        .unwrap_or("");
+
+    print!("{}", render_flat(&flatten_file(file_path, field_path)?));
+    Ok(())
+}
+
+/// Handle unflatten command logic
+pub fn handle_unflatten_command(matches: &ArgMatches) -> Result<()> {
+    let file_path = matches.get_one::<String>("file").unwrap();
+    let document = unflatten_str(&read_input(file_path)?, file_path)?;
+
+    let output = toml::to_string(&document)?;
+    match matches.get_one::<String>("output-file") {
+        Some(output_file) => write_file(output_file, &output),
+        None => {
+            print!("{}", output);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,548 @@
+//! Core functionality for fmt command
+//!
+//! Formatting normalizes the whitespace around keys, values and table
+//! headers while keeping every comment next to the item it describes. Keys
+//! can optionally be sorted, long arrays wrapped one element per line and
+//! tables switched between inline and standard form. Formatting an already
+//! formatted document leaves it unchanged.
+
+use std::str::FromStr;
+use toml_edit::{Array, Decor, Item, RawString, Table, Value};
+
+use crate::document::{parse_toml_edit, read_file, write_file};
+use crate::error::{Result, TomlExtractError};
+use crate::path::{parse_path, PathSegment};
+
+/// Preference between inline tables and standard `[table]` sections
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableStyle {
+    /// Leave tables as written
+    Keep,
+    /// Write nested tables that only hold values (`[dependencies.serde]`) inline
+    Inline,
+    /// Write inline tables as standard sections
+    Expand,
+}
+
+impl FromStr for TableStyle {
+    type Err = TomlExtractError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "keep" => Ok(Self::Keep),
+            "inline" => Ok(Self::Inline),
+            "expand" => Ok(Self::Expand),
+            other => Err(TomlExtractError::InvalidArgument(format!(
+                "Unknown table style: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Options for [`format_str`]
+#[derive(Debug, Clone)]
+pub struct FmtOptions {
+    /// Sort keys: `None` keeps the written order, an empty list sorts every
+    /// table and otherwise only the listed table paths (and their sub-tables)
+    pub sort_keys: Option<Vec<String>>,
+    /// Wrap arrays one element per line once `key = [...]` would be longer
+    pub array_width: usize,
+    /// Inline or standard tables
+    pub tables: TableStyle,
+    /// Indentation of the elements of wrapped arrays
+    pub indent: usize,
+    /// Blank lines before each table header
+    pub blank_lines: usize,
+}
+
+impl Default for FmtOptions {
+    fn default() -> Self {
+        Self {
+            sort_keys: None,
+            array_width: 80,
+            tables: TableStyle::Keep,
+            indent: 4,
+            blank_lines: 1,
+        }
+    }
+}
+
+/// Format a TOML file in place
+///
+/// Returns whether the file changed; unchanged files are not rewritten.
+pub fn format_file(file_path: &str, options: &FmtOptions) -> Result<bool> {
+    let content = read_file(file_path)?;
+    let formatted = format_str(&content, file_path, options)?;
+    if formatted == content {
+        return Ok(false);
+    }
+    write_file(file_path, &formatted)?;
+    Ok(true)
+}
+
+/// Check whether a TOML file is already formatted
+pub fn check_file(file_path: &str, options: &FmtOptions) -> Result<bool> {
+    let content = read_file(file_path)?;
+    Ok(format_str(&content, file_path, options)? == content)
+}
+
+/// Format TOML source text
+///
+/// `file_path` is only used for error reporting.
+pub fn format_str(content: &str, file_path: &str, options: &FmtOptions) -> Result<String> {
+    let mut document = parse_toml_edit(content, file_path)?;
+    let root = document.as_table_mut();
+
+    match options.tables {
+        TableStyle::Keep => {}
+        TableStyle::Inline => inline_leaf_tables(root, 0),
+        TableStyle::Expand => expand_inline_tables(root),
+    }
+
+    match options.sort_keys.as_deref() {
+        None => {}
+        Some([]) => sort_table(root),
+        Some(paths) => {
+            for path in paths {
+                let table = table_at_mut(root, &parse_path(path)?)
+                    .ok_or_else(|| TomlExtractError::NotATable(path.clone()))?;
+                sort_table(table);
+            }
+        }
+    }
+
+    normalize_table(root, options, true);
+    let trailing = comment_block(raw_str(Some(document.trailing())), false);
+    document.set_trailing(if trailing.is_empty() {
+        trailing
+    } else {
+        format!("\n{}", trailing)
+    });
+
+    let formatted = document.to_string();
+    let formatted = formatted.trim_start_matches('\n').trim_end();
+    Ok(if formatted.is_empty() {
+        String::new()
+    } else {
+        format!("{}\n", formatted)
+    })
+}
+
+/// Find the standard table at `segments`, indexing into arrays of tables
+pub(crate) fn table_at_mut<'a>(
+    table: &'a mut Table,
+    segments: &[PathSegment],
+) -> Option<&'a mut Table> {
+    let Some((first, rest)) = segments.split_first() else {
+        return Some(table);
+    };
+    let PathSegment::Key(key) = first else {
+        return None;
+    };
+    match (table.get_mut(key)?, rest.split_first()) {
+        (Item::ArrayOfTables(array), Some((PathSegment::Index(index), rest))) => {
+            table_at_mut(array.get_mut(*index)?, rest)
+        }
+        (Item::Table(child), _) => table_at_mut(child, rest),
+        _ => None,
+    }
+}
+
+/// Sort the keys of a table and all tables below it
+///
+/// Sub-table headers are reordered too, by handing the header positions the
+/// subtree already occupies back out in sorted order.
+pub(crate) fn sort_table(table: &mut Table) {
+    sort_recursive(table);
+    renumber_tables(table);
+}
+
+fn sort_recursive(table: &mut Table) {
+    table.sort_values();
+    for (_, item) in table.iter_mut() {
+        match item {
+            Item::Table(child) => sort_recursive(child),
+            Item::ArrayOfTables(array) => array.iter_mut().for_each(sort_recursive),
+            _ => {}
+        }
+    }
+}
+
+/// Give the headers below `table` their existing positions in key order
+pub(crate) fn renumber_tables(table: &mut Table) {
+    let mut positions = Vec::new();
+    collect_positions(table, &mut positions);
+    positions.sort_unstable();
+    assign_positions(table, &mut positions.into_iter());
+}
+
+pub(crate) fn collect_positions(table: &Table, positions: &mut Vec<usize>) {
+    for (_, item) in table.iter() {
+        let children: Vec<&Table> = match item {
+            Item::Table(child) if !child.is_dotted() => vec![child],
+            Item::ArrayOfTables(array) => array.iter().collect(),
+            _ => continue,
+        };
+        for child in children {
+            positions.extend(child.position());
+            collect_positions(child, positions);
+        }
+    }
+}
+
+pub(crate) fn assign_positions(table: &mut Table, positions: &mut impl Iterator<Item = usize>) {
+    for (_, item) in table.iter_mut() {
+        let children: Vec<&mut Table> = match item {
+            Item::Table(child) if !child.is_dotted() => vec![child],
+            Item::ArrayOfTables(array) => array.iter_mut().collect(),
+            _ => continue,
+        };
+        for child in children {
+            if child.position().is_some() {
+                if let Some(position) = positions.next() {
+                    child.set_position(position);
+                }
+            }
+            assign_positions(child, positions);
+        }
+    }
+}
+
+/// Turn tables below the top level that only hold values into inline tables
+///
+/// Tables with comments are left alone, since inline tables cannot hold them.
+fn inline_leaf_tables(table: &mut Table, depth: usize) {
+    let mut inlined_position = None;
+    for (_, item) in table.iter_mut() {
+        match item {
+            Item::Table(child) if !child.is_dotted() => {
+                let leaf = child.iter().all(|(_, item)| item.is_value());
+                if depth == 0 || !leaf || table_has_comments(child) {
+                    inline_leaf_tables(child, depth + 1);
+                    continue;
+                }
+                inlined_position = inlined_position.or(child.position());
+                let child = std::mem::take(child);
+                *item = Item::Value(Value::InlineTable(child.into_inline_table()));
+            }
+            Item::ArrayOfTables(array) => {
+                for child in array.iter_mut() {
+                    inline_leaf_tables(child, depth + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // An implicit parent such as `dependencies` in `[dependencies.serde]`
+    // now holds a value, so it needs a header of its own
+    if let Some(position) = inlined_position {
+        if table.is_implicit() {
+            table.set_implicit(false);
+            if table.position().is_none() {
+                table.set_position(position);
+            }
+        }
+    }
+}
+
+fn table_has_comments(table: &Table) -> bool {
+    has_comment(table.decor())
+        || table.iter().any(|(key, _)| {
+            table
+                .key(key)
+                .is_some_and(|key| has_comment(key.leaf_decor()))
+        })
+        || table
+            .get_values()
+            .iter()
+            .any(|(_, value)| has_comment(value.decor()))
+}
+
+/// Turn inline tables that are the value of a key into standard tables
+///
+/// Comments above the key move above the new header, and a comment after
+/// the inline table follows the header.
+fn expand_inline_tables(table: &mut Table) {
+    for (mut key, item) in table.iter_mut() {
+        if let Item::Value(Value::InlineTable(inline)) = item {
+            let prefix = raw_str(key.leaf_decor().prefix()).to_string();
+            let suffix = trailing_comment(raw_str(inline.decor().suffix()));
+            let mut expanded = std::mem::take(inline).into_table();
+            expanded.decor_mut().set_prefix(prefix);
+            expanded.decor_mut().set_suffix(suffix);
+            *item = Item::Table(expanded);
+            key.leaf_decor_mut().clear();
+        }
+        match item {
+            Item::Table(child) if !child.is_dotted() => expand_inline_tables(child),
+            Item::ArrayOfTables(array) => array.iter_mut().for_each(expand_inline_tables),
+            _ => {}
+        }
+    }
+}
+
+fn normalize_table(table: &mut Table, options: &FmtOptions, is_root: bool) {
+    if !is_root {
+        let decor = table.decor_mut();
+        let comments = comment_block(raw_str(decor.prefix()), false);
+        let suffix = trailing_comment(raw_str(decor.suffix()));
+        decor.set_prefix(format!("{}{}", "\n".repeat(options.blank_lines), comments));
+        decor.set_suffix(suffix);
+    }
+
+    let mut first = true;
+    for (mut key, item) in table.iter_mut() {
+        match item {
+            Item::Value(value) => {
+                let decor = key.leaf_decor_mut();
+                let comments = comment_block(raw_str(decor.prefix()), !first);
+                decor.set_prefix(comments);
+                decor.set_suffix(" ");
+                normalize_value(value, options, key.display_repr().len() + 3);
+                first = false;
+            }
+            Item::Table(child) if child.is_dotted() => {
+                normalize_dotted(child, options);
+                first = false;
+            }
+            Item::Table(child) => normalize_table(child, options, false),
+            Item::ArrayOfTables(array) => {
+                for child in array.iter_mut() {
+                    normalize_table(child, options, false);
+                }
+            }
+            Item::None => {}
+        }
+    }
+}
+
+/// Normalize the values of dotted keys (`a.b = 1`), leaving the keys as written
+fn normalize_dotted(table: &mut Table, options: &FmtOptions) {
+    for (key, item) in table.iter_mut() {
+        match item {
+            Item::Value(value) => normalize_value(value, options, key.display_repr().len() + 3),
+            Item::Table(child) => normalize_dotted(child, options),
+            _ => {}
+        }
+    }
+}
+
+fn normalize_value(value: &mut Value, options: &FmtOptions, offset: usize) {
+    let suffix = trailing_comment(raw_str(value.decor().suffix()));
+    match value {
+        Value::Array(array) => format_array(array, options, offset),
+        Value::InlineTable(_) => compact_value(value),
+        _ => {}
+    }
+    let decor = value.decor_mut();
+    decor.set_prefix(" ");
+    decor.set_suffix(suffix);
+}
+
+/// Put an array on one line, or one element per line if that is too long
+///
+/// Arrays with comments between their elements are kept as written.
+fn format_array(array: &mut Array, options: &FmtOptions, offset: usize) {
+    let commented = has_comment_str(raw_str(Some(array.trailing())))
+        || array.iter().any(|value| has_comment(value.decor()));
+    if commented {
+        return;
+    }
+
+    array.iter_mut().for_each(compact_value);
+    array.fmt();
+    if array.is_empty() || offset + array.to_string().trim().len() <= options.array_width {
+        return;
+    }
+
+    for value in array.iter_mut() {
+        let decor = value.decor_mut();
+        decor.set_prefix(format!("\n{}", " ".repeat(options.indent)));
+        decor.set_suffix("");
+    }
+    array.set_trailing_comma(true);
+    array.set_trailing("\n");
+}
+
+/// Put a nested array or inline table on a single line
+fn compact_value(value: &mut Value) {
+    match value {
+        Value::Array(array) => {
+            array.iter_mut().for_each(compact_value);
+            array.fmt();
+        }
+        Value::InlineTable(table) => {
+            for (_, value) in table.iter_mut() {
+                compact_value(value);
+            }
+            table.fmt();
+        }
+        _ => {}
+    }
+}
+
+/// Keep the comment lines of a decor prefix, dropping indentation
+///
+/// Runs of blank lines collapse to one; a leading one is only kept when
+/// `leading_blank` is set.
+fn comment_block(raw: &str, leading_blank: bool) -> String {
+    let mut out = String::new();
+    let mut blank = false;
+    // The text after the last newline is the indentation of the item itself
+    let lines = raw.rsplit_once('\n').map_or("", |(lines, _)| lines);
+    for line in lines.split('\n').map(str::trim) {
+        if line.is_empty() {
+            blank = leading_blank || !out.is_empty();
+            continue;
+        }
+        if blank {
+            out.push('\n');
+            blank = false;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    if blank && raw.contains('\n') {
+        out.push('\n');
+    }
+    out
+}
+
+/// Keep the comment of a decor suffix, separated by a single space
+fn trailing_comment(raw: &str) -> String {
+    match raw.find('#') {
+        Some(start) => format!(" {}", raw[start..].trim_end()),
+        None => String::new(),
+    }
+}
+
+pub(crate) fn has_comment(decor: &Decor) -> bool {
+    has_comment_str(raw_str(decor.prefix())) || has_comment_str(raw_str(decor.suffix()))
+}
+
+fn has_comment_str(raw: &str) -> bool {
+    raw.contains('#')
+}
+
+fn raw_str(raw: Option<&RawString>) -> &str {
+    raw.and_then(RawString::as_str).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"# Package manifest
+
+  [package]
+name="demo"   # the name
+version =  "1.0"
+keywords = [ "toml","cli" ]
+
+
+
+# Runtime dependencies
+[dependencies]
+toml   = "0.8"
+serde = {version="1.0",features=["derive"]}
+
+[dependencies.clap]
+version = "4"
+"#;
+
+    fn format(content: &str, options: &FmtOptions) -> String {
+        format_str(content, "Cargo.toml", options).unwrap()
+    }
+
+    #[test]
+    fn test_format_keeps_comments() {
+        let formatted = format(SAMPLE, &FmtOptions::default());
+        assert_eq!(
+            formatted,
+            r#"# Package manifest
+
+[package]
+name = "demo" # the name
+version = "1.0"
+keywords = ["toml", "cli"]
+
+# Runtime dependencies
+[dependencies]
+toml = "0.8"
+serde = { version = "1.0", features = ["derive"] }
+
+[dependencies.clap]
+version = "4"
+"#
+        );
+        assert_eq!(format(&formatted, &FmtOptions::default()), formatted);
+    }
+
+    #[test]
+    fn test_format_options() {
+        let options = FmtOptions {
+            sort_keys: Some(vec!["dependencies".to_string()]),
+            array_width: 20,
+            tables: TableStyle::Inline,
+            indent: 2,
+            blank_lines: 2,
+        };
+        let formatted = format(SAMPLE, &options);
+        assert_eq!(
+            formatted,
+            r#"# Package manifest
+
+[package]
+name = "demo" # the name
+version = "1.0"
+keywords = [
+  "toml",
+  "cli",
+]
+
+
+# Runtime dependencies
+[dependencies]
+clap = { version = "4" }
+serde = { version = "1.0", features = ["derive"] }
+toml = "0.8"
+"#
+        );
+        assert_eq!(format(&formatted, &options), formatted);
+    }
+
+    #[test]
+    fn test_format_expand_and_sort_all() {
+        let options = FmtOptions {
+            sort_keys: Some(Vec::new()),
+            tables: TableStyle::Expand,
+            ..FmtOptions::default()
+        };
+        let formatted = format("z = 1\n[b]\nx = { k = 1 }\n[a]\ny = 2\n", &options);
+        assert_eq!(formatted, "z = 1\n\n[a]\ny = 2\n\n[b]\n\n[b.x]\nk = 1\n");
+        assert_eq!(format(&formatted, &options), formatted);
+
+        let formatted = format(
+            "[b]\n# about x\nx = { k = 1 } # trailing\ny = 2\n",
+            &options,
+        );
+        assert_eq!(
+            formatted,
+            "[b]\ny = 2\n\n# about x\n[b.x] # trailing\nk = 1\n"
+        );
+        assert_eq!(format(&formatted, &options), formatted);
+        assert!(matches!(
+            format_str(
+                "[a]\n",
+                "x.toml",
+                &FmtOptions {
+                    sort_keys: Some(vec!["missing".to_string()]),
+                    ..FmtOptions::default()
+                }
+            ),
+            Err(TomlExtractError::NotATable(_))
+        ));
+    }
+}
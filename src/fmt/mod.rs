@@ -0,0 +1,14 @@
+//! Canonical formatting of TOML files
+//!
+//! Unlike the commands that rewrite whole files through `toml`, formatting
+//! works on a [`toml_edit`] document, so comments survive and only layout
+//! changes.
+
+pub mod core;
+#[cfg(feature = "cli")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cli")))]
+pub mod xcli;
+
+pub use core::*;
+#[cfg(feature = "cli")]
+pub use xcli::*;
@@ -0,0 +1,114 @@
+//! CLI definitions and handling for fmt command
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+
+use super::core::*;
+use crate::error::Result;
+
+/// Define the fmt command CLI structure
+pub fn fmt_command() -> Command {
+    Command::new("fmt")
+        .about("Format TOML files in place, keeping comments")
+        .arg(
+            Arg::new("files")
+                .value_name("FILE")
+                .help("TOML files to format (Cargo.toml if none are given)")
+                .num_args(1..),
+        )
+        .arg(
+            Arg::new("file")
+                .short('f')
+                .long("file")
+                .value_name("FILE")
+                .help("TOML file to format (repeat for several files)")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("check")
+                .long("check")
+                .help("Only list files that are not formatted (exit status 1 if any)")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("sort-keys")
+                .long("sort-keys")
+                .value_name("TABLE")
+                .help("Sort keys of every table, or only of the given tables (e.g. --sort-keys=dependencies,features)")
+                .num_args(0..)
+                .require_equals(true)
+                .value_delimiter(','),
+        )
+        .arg(
+            Arg::new("array-width")
+                .long("array-width")
+                .value_name("N")
+                .help("Put array elements on separate lines once `key = [...]` is longer than N")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("80"),
+        )
+        .arg(
+            Arg::new("tables")
+                .long("tables")
+                .value_name("STYLE")
+                .help("Keep tables as written, inline nested value-only tables, or expand inline tables")
+                .value_parser(["keep", "inline", "expand"])
+                .default_value("keep"),
+        )
+        .arg(
+            Arg::new("indent")
+                .long("indent")
+                .value_name("N")
+                .help("Indentation of wrapped array elements")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("4"),
+        )
+        .arg(
+            Arg::new("blank-lines")
+                .long("blank-lines")
+                .value_name("N")
+                .help("Blank lines before each table header")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("1"),
+        )
+}
+
+/// Handle fmt command logic
+///
+/// With `--check`, returns `Ok(false)` when some file is not formatted; the
+/// caller turns that into exit status 1.
+pub fn handle_fmt_command(matches: &ArgMatches) -> Result<bool> {
+    let options = FmtOptions {
+        sort_keys: matches.contains_id("sort-keys").then(|| {
+            matches
+                .get_many::<String>("sort-keys")
+                .map(|tables| tables.cloned().collect())
+                .unwrap_or_default()
+        }),
+        array_width: *matches.get_one::<usize>("array-width").unwrap(),
+        tables: matches.get_one::<String>("tables").unwrap().parse()?,
+        indent: *matches.get_one::<usize>("indent").unwrap(),
+        blank_lines: *matches.get_one::<usize>("blank-lines").unwrap(),
+    };
+
+    let mut files: Vec<&String> = matches
+        .get_many::<String>("file")
+        .into_iter()
+        .flatten()
+        .chain(matches.get_many::<String>("files").into_iter().flatten())
+        .collect();
+    let default_file = "Cargo.toml".to_string();
+    if files.is_empty() {
+        files.push(&default_file);
+    }
+
+    let mut formatted = true;
+    for file_path in files {
+        if !matches.get_flag("check") {
+            format_file(file_path, &options)?;
+        } else if !check_file(file_path, &options)? {
+            println!("{}", file_path);
+            formatted = false;
+        }
+    }
+    Ok(formatted)
+}
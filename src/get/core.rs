@@ -1,33 +1,51 @@
 //! Core functionality for get command
 
-use anyhow::{Context, Result};
+#[cfg(feature = "cargo-presets")]
 use std::collections::HashMap;
-use std::fs;
 use toml::Value as TomlValue;
 
-use super::types::{ExtractConfig, ExtractionResult};
-use crate::error::TomlExtractError;
-use crate::get::utils::{format_output, get_nested_value, strip_quotes_internal};
+use super::types::ExtractConfig;
+#[cfg(feature = "json")]
+use super::types::ExtractionResult;
+use crate::document::load_toml;
+use crate::error::{Result, TomlExtractError};
+#[cfg(feature = "json")]
+use crate::get::utils::to_json_value;
+use crate::get::utils::{
+    format_output, format_toml_fragment, get_nested_value, strip_quotes_internal, type_name,
+};
+use crate::path::split_unquoted;
 
 /// Extract a single field from a TOML file
 ///
 /// # Errors
 /// Returns `Err` if:
-/// - The file doesn't exist or can't be read
-/// - The field path doesn't exist
-/// - The TOML syntax is invalid
-/// - Array index is out of bounds
+/// - The file doesn't exist or can't be read ([`TomlExtractError::FileNotFound`], [`TomlExtractError::Io`])
+/// - The TOML syntax is invalid ([`TomlExtractError::InvalidToml`])
+/// - The field path doesn't exist ([`TomlExtractError::FieldNotFound`])
+/// - Array index is out of bounds ([`TomlExtractError::ArrayIndexOutOfBounds`])
+///
+/// When `config.default` is set, a missing field is taken to be that string
+/// and formatted like a found value instead of failing.
 pub fn extract_field(config: &ExtractConfig) -> Result<String> {
-    let content = fs::read_to_string(&config.file_path)
-        .context(format!("Failed to read file: {}", config.file_path))?;
-
-    let value: TomlValue = toml::from_str(&content)
-        .context(format!("Invalid TOML syntax in: {}", config.file_path))?;
-
-    let field_value = get_nested_value(&value, &config.field_path)
-        .context(format!("Field not found: {}", config.field_path))?;
+    let value = load_toml(&config.file_path)?;
+    let default_value;
+    let field_value = match (
+        get_nested_value(&value, &config.field_path),
+        &config.default,
+    ) {
+        (Ok(field_value), _) => field_value,
+        (Err(e), Some(default)) if e.is_not_found() => {
+            default_value = TomlValue::String(default.clone());
+            &default_value
+        }
+        (Err(e), _) => return Err(e),
+    };
 
-    let mut result = format_output(field_value, config.output_format.as_deref())?;
+    let mut result = match config.output_format.as_deref() {
+        Some("toml") => format_toml_fragment(&config.field_path, field_value)?,
+        output_format => format_output(field_value, output_format)?,
+    };
 
     if config.strip_quotes {
         result = strip_quotes_internal(&result);
@@ -38,41 +56,58 @@ pub fn extract_field(config: &ExtractConfig) -> Result<String> {
 
 /// Extract multiple fields from a TOML file
 ///
+/// Each entry is a field path, optionally followed by `=DEFAULT`
+/// (e.g. `package.license=UNLICENSED`). Missing fields take their default (as
+/// a JSON string) if one is given and are otherwise listed in
+/// [`ExtractionResult::missing`]; they do not abort the extraction.
+///
 /// # Arguments
 /// * `file_path` - Path to the TOML file
-/// * `field_paths` - List of field paths to extract
-/// * `strip_quotes` - Whether to strip quotes from string values
+/// * `field_paths` - List of field paths to extract, each with an optional default
 ///
 /// # Returns
-/// An `ExtractionResult` containing all extracted fields and their values
+/// An `ExtractionResult` holding the typed values in request order
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
 pub fn extract_multiple_fields(
     file_path: &str,
     field_paths: &[String],
-    strip_quotes: bool,
 ) -> Result<ExtractionResult> {
-    let content =
-        fs::read_to_string(file_path).context(format!("Failed to read file: {}", file_path))?;
-
-    let value: TomlValue =
-        toml::from_str(&content).context(format!("Invalid TOML syntax in: {}", file_path))?;
-
+    let value = load_toml(file_path)?;
     let mut result = ExtractionResult::new(file_path.to_string());
 
-    for field_path in field_paths {
-        let field_value = get_nested_value(&value, field_path)
-            .context(format!("Field not found: {}", field_path))?;
+    for spec in field_paths {
+        let (field_path, default) = split_default(spec);
 
-        let mut formatted_value = format_output(field_value, None)?;
-        if strip_quotes {
-            formatted_value = strip_quotes_internal(&formatted_value);
+        match get_nested_value(&value, field_path) {
+            Ok(field_value) => {
+                result.add_field(field_path.to_string(), to_json_value(field_value)?)
+            }
+            Err(e) if e.is_not_found() => match default {
+                Some(default) => result.add_field(
+                    field_path.to_string(),
+                    serde_json::Value::String(default.to_string()),
+                ),
+                None => result.add_missing(field_path.to_string()),
+            },
+            Err(e) => return Err(e),
         }
-
-        result.add_field(field_path.clone(), formatted_value);
     }
 
     Ok(result)
 }
 
+/// Split a `path=default` field spec into its path and optional default
+///
+/// The split is at the first `=` outside a quoted key, so `"a=b"=x` is the
+/// key `a=b` with the default `x`.
+pub fn split_default(spec: &str) -> (&str, Option<&str>) {
+    match split_unquoted(spec, '=') {
+        Some((path, default)) => (path, Some(default)),
+        None => (spec, None),
+    }
+}
+
 /// Extract an array from a TOML file
 ///
 /// # Arguments
@@ -92,6 +127,7 @@ pub fn extract_array(
         field_path: array_path.to_string(),
         output_format: output_format.map(|s| s.to_string()),
         strip_quotes: false,
+        default: None,
     };
     extract_field(&config)
 }
@@ -105,18 +141,16 @@ pub fn extract_array(
 /// # Returns
 /// The length of the array as a `usize`
 pub fn extract_array_length(file_path: &str, array_path: &str) -> Result<usize> {
-    let content =
-        fs::read_to_string(file_path).context(format!("Failed to read file: {}", file_path))?;
-
-    let value: TomlValue =
-        toml::from_str(&content).context(format!("Invalid TOML syntax in: {}", file_path))?;
-
-    let array_value =
-        get_nested_value(&value, array_path).context(format!("Array not found: {}", array_path))?;
+    let value = load_toml(file_path)?;
+    let array_value = get_nested_value(&value, array_path)?;
 
     let array = array_value
         .as_array()
-        .ok_or_else(|| TomlExtractError::NotAnArray(array_path.to_string()))?;
+        .ok_or_else(|| TomlExtractError::TypeMismatch {
+            path: array_path.to_string(),
+            expected: "array".to_string(),
+            found: type_name(array_value).to_string(),
+        })?;
 
     Ok(array.len())
 }
@@ -137,26 +171,23 @@ pub fn extract_array_element(
     index: usize,
     strip_quotes: bool,
 ) -> Result<String> {
-    let content =
-        fs::read_to_string(file_path).context(format!("Failed to read file: {}", file_path))?;
-
-    let value: TomlValue =
-        toml::from_str(&content).context(format!("Invalid TOML syntax in: {}", file_path))?;
-
-    let array_value =
-        get_nested_value(&value, array_path).context(format!("Array not found: {}", array_path))?;
+    let value = load_toml(file_path)?;
+    let array_value = get_nested_value(&value, array_path)?;
 
     let array = array_value
         .as_array()
-        .ok_or_else(|| TomlExtractError::NotAnArray(array_path.to_string()))?;
+        .ok_or_else(|| TomlExtractError::TypeMismatch {
+            path: array_path.to_string(),
+            expected: "array".to_string(),
+            found: type_name(array_value).to_string(),
+        })?;
 
     if index >= array.len() {
         return Err(TomlExtractError::ArrayIndexOutOfBounds {
             path: array_path.to_string(),
             index,
             length: array.len(),
-        }
-        .into());
+        });
     }
 
     let element = &array[index];
@@ -169,7 +200,7 @@ pub fn extract_array_element(
     Ok(result)
 }
 
-// Preset extraction functions
+// Preset extraction functions (`cargo-presets` feature)
 
 /// Get the package name from a Cargo.toml file
 ///
@@ -178,6 +209,8 @@ pub fn extract_array_element(
 ///
 /// # Returns
 /// The package name as a string
+#[cfg(feature = "cargo-presets")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cargo-presets")))]
 pub fn get_package_name(file_path: Option<&str>) -> Result<String> {
     let path = file_path.unwrap_or("Cargo.toml");
     let config = ExtractConfig {
@@ -185,6 +218,7 @@ pub fn get_package_name(file_path: Option<&str>) -> Result<String> {
         field_path: "package.name".to_string(),
         output_format: None,
         strip_quotes: true,
+        default: None,
     };
     extract_field(&config)
 }
@@ -196,6 +230,8 @@ pub fn get_package_name(file_path: Option<&str>) -> Result<String> {
 ///
 /// # Returns
 /// The package version as a string
+#[cfg(feature = "cargo-presets")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cargo-presets")))]
 pub fn get_package_version(file_path: Option<&str>) -> Result<String> {
     let path = file_path.unwrap_or("Cargo.toml");
     let config = ExtractConfig {
@@ -203,6 +239,7 @@ pub fn get_package_version(file_path: Option<&str>) -> Result<String> {
         field_path: "package.version".to_string(),
         output_format: None,
         strip_quotes: true,
+        default: None,
     };
     extract_field(&config)
 }
@@ -214,11 +251,11 @@ pub fn get_package_version(file_path: Option<&str>) -> Result<String> {
 ///
 /// # Returns
 /// A HashMap of dependency names to their versions
+#[cfg(feature = "cargo-presets")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cargo-presets")))]
 pub fn get_dependencies(file_path: Option<&str>) -> Result<HashMap<String, String>> {
     let path = file_path.unwrap_or("Cargo.toml");
-    let content = fs::read_to_string(path).context("Failed to read Cargo.toml")?;
-
-    let value: TomlValue = toml::from_str(&content).context("Invalid TOML syntax in Cargo.toml")?;
+    let value = load_toml(path)?;
 
     let mut dependencies = HashMap::new();
 
@@ -253,6 +290,8 @@ pub fn get_dependencies(file_path: Option<&str>) -> Result<HashMap<String, Strin
 ///
 /// # Returns
 /// The authors (or specific author) as a string
+#[cfg(feature = "cargo-presets")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cargo-presets")))]
 pub fn get_package_authors(
     file_path: Option<&str>,
     index: Option<usize>,
@@ -276,6 +315,8 @@ pub fn get_package_authors(
 ///
 /// # Returns
 /// The keywords (or specific keyword) as a string
+#[cfg(feature = "cargo-presets")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cargo-presets")))]
 pub fn get_package_keywords(
     file_path: Option<&str>,
     index: Option<usize>,
@@ -299,6 +340,8 @@ pub fn get_package_keywords(
 ///
 /// # Returns
 /// The categories (or specific category) as a string
+#[cfg(feature = "cargo-presets")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cargo-presets")))]
 pub fn get_package_categories(
     file_path: Option<&str>,
     index: Option<usize>,
@@ -316,7 +359,6 @@ pub fn get_package_categories(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
     use std::io::Write;
     use tempfile::NamedTempFile;
 
@@ -352,19 +394,129 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "json")]
     fn test_extract_multiple_fields() {
         let mut temp_file = NamedTempFile::new().unwrap();
         writeln!(temp_file, "[package]\nname = \"test\"\nversion = \"1.0.0\"").unwrap();
         let path = temp_file.path().to_str().unwrap();
 
-        let fields = vec!["package.name".to_string(), "package.version".to_string()];
-        let result = extract_multiple_fields(path, &fields, false).unwrap();
+        let fields = vec!["package.version".to_string(), "package.name".to_string()];
+        let result = extract_multiple_fields(path, &fields).unwrap();
 
-        assert_eq!(result.get("package.name"), Some(&"\"test\"".to_string()));
+        assert_eq!(result.get("package.name"), Some(&serde_json::json!("test")));
         assert_eq!(
             result.get("package.version"),
-            Some(&"\"1.0.0\"".to_string())
+            Some(&serde_json::json!("1.0.0"))
+        );
+        assert_eq!(
+            result.fields.keys().collect::<Vec<_>>(),
+            ["package.version", "package.name"]
+        );
+        assert_eq!(
+            result.to_json().unwrap(),
+            r#"{"fields":{"package.version":"1.0.0","package.name":"test"},"missing":[]}"#
+        );
+    }
+
+    #[test]
+    fn test_extract_field_typed_errors() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(
+            temp_file,
+            "[package]\nname = \"test\"\nauthors = [\"Alice\"]"
+        )
+        .unwrap();
+        let path = temp_file.path().to_str().unwrap();
+
+        let config = ExtractConfig {
+            file_path: path.to_string(),
+            field_path: "package.missing".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            extract_field(&config),
+            Err(TomlExtractError::FieldNotFound { .. })
+        ));
+
+        let config = ExtractConfig {
+            file_path: path.to_string(),
+            field_path: "package.authors[3]".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            extract_field(&config),
+            Err(TomlExtractError::ArrayIndexOutOfBounds { index: 3, .. })
+        ));
+
+        assert!(matches!(
+            extract_array_length(path, "package.name"),
+            Err(TomlExtractError::TypeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_extract_defaults() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "[package]\nname = \"test\"").unwrap();
+        let path = temp_file.path().to_str().unwrap();
+
+        let config = ExtractConfig {
+            file_path: path.to_string(),
+            field_path: "package.description".to_string(),
+            default: Some("".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(extract_field(&config).unwrap(), "\"\"");
+
+        let config = ExtractConfig {
+            default: Some("fb".to_string()),
+            output_format: Some("json".to_string()),
+            ..config
+        };
+        #[cfg(feature = "json")]
+        assert_eq!(extract_field(&config).unwrap(), "\"fb\"");
+        let config = ExtractConfig {
+            output_format: Some("lines".to_string()),
+            ..config
+        };
+        assert_eq!(extract_field(&config).unwrap(), "fb");
+        let config = ExtractConfig {
+            output_format: Some("toml".to_string()),
+            ..config
+        };
+        assert_eq!(
+            extract_field(&config).unwrap(),
+            "[package]\ndescription = \"fb\""
         );
+
+        #[cfg(feature = "json")]
+        {
+            let fields = vec![
+                "package.name".to_string(),
+                "package.license=UNLICENSED".to_string(),
+                "package.version".to_string(),
+                "\"a=b\"=x".to_string(),
+            ];
+            let result = extract_multiple_fields(path, &fields).unwrap();
+            assert_eq!(result.get("package.name"), Some(&serde_json::json!("test")));
+            assert_eq!(
+                result.get("package.license"),
+                Some(&serde_json::json!("UNLICENSED"))
+            );
+            assert_eq!(result.get("\"a=b\""), Some(&serde_json::json!("x")));
+            assert_eq!(result.missing, vec!["package.version".to_string()]);
+            assert!(!result.is_complete());
+            #[cfg(feature = "yaml")]
+            assert_eq!(
+                result.to_yaml().unwrap(),
+                "fields:\n  package.name: test\n  package.license: UNLICENSED\n  '\"a=b\"': x\nmissing:\n- package.version\n"
+            );
+            // A missing field keeps its place as an empty record
+            assert_eq!(
+                result.to_nul(&["package.name", "package.version", "package.license"]),
+                "test\0\0UNLICENSED\0"
+            );
+        }
     }
 
     #[test]
@@ -377,6 +529,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "cargo-presets")]
     fn test_get_dependencies() {
         let mut temp_file = NamedTempFile::new().unwrap();
         writeln!(
@@ -387,7 +540,7 @@ mod tests {
         let path = temp_file.path().to_str().unwrap();
 
         let deps = get_dependencies(Some(path)).unwrap();
-        let expected = HashMap::from([
+        let expected = std::collections::HashMap::from([
             ("serde".to_string(), "1.0".to_string()),
             ("toml".to_string(), "0.8".to_string()),
         ]);
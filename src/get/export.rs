@@ -0,0 +1,429 @@
+//! Environment-style output formats for get command
+//!
+//! Values are flattened into `KEY=value` pairs: nested tables and arrays are
+//! walked down to their leaves, and each leaf's path becomes a variable name
+//! (`package.name` → `PACKAGE_NAME`, `package.authors[0]` → `PACKAGE_AUTHORS_0`).
+
+use std::fmt::Write as _;
+use std::fs::OpenOptions;
+use std::io::Write as _;
+use std::str::FromStr;
+use toml::Value as TomlValue;
+
+use crate::document::{load_toml, write_file};
+use crate::error::{Result, TomlExtractError};
+use crate::get::core::split_default;
+use crate::get::utils::{format_raw, get_nested_value};
+use crate::path::{format_path, parse_path, PathSegment};
+
+/// Target syntax for exported variables
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// `KEY=value`, quoted as for [`ExportFormat::Dotenv`] unless the value
+    /// is a plain word
+    Env,
+    /// `export KEY='value'`, safe to `eval`
+    Shell,
+    /// `KEY='value'` lines for a `.env` file
+    Dotenv,
+    /// `KEY<<DELIMITER` blocks for the file named by `$GITHUB_OUTPUT`
+    Gha,
+}
+
+impl FromStr for ExportFormat {
+    type Err = TomlExtractError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "env" => Ok(Self::Env),
+            "shell" => Ok(Self::Shell),
+            "dotenv" => Ok(Self::Dotenv),
+            "gha" => Ok(Self::Gha),
+            other => Err(TomlExtractError::InvalidArgument(format!(
+                "Unknown export format: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Case applied to exported variable names
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyCase {
+    /// `PACKAGE_NAME`
+    Upper,
+    /// `package_name`
+    Lower,
+    /// Keep the case used in the TOML file
+    Preserve,
+}
+
+impl FromStr for KeyCase {
+    type Err = TomlExtractError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "upper" => Ok(Self::Upper),
+            "lower" => Ok(Self::Lower),
+            "preserve" => Ok(Self::Preserve),
+            other => Err(TomlExtractError::InvalidArgument(format!(
+                "Unknown key case: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Options controlling how field paths become variable names
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    /// Prepended verbatim to every variable name
+    pub prefix: String,
+    /// Joins the segments of a flattened path
+    pub separator: String,
+    /// Case applied to variable names (the prefix included)
+    pub key_case: KeyCase,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            prefix: String::new(),
+            separator: "_".to_string(),
+            key_case: KeyCase::Upper,
+        }
+    }
+}
+
+/// Resolve fields from a TOML file and flatten them into variables
+///
+/// Each entry of `field_specs` is a field path with an optional `=DEFAULT`,
+/// as for [`extract_multiple_fields`](crate::get::extract_multiple_fields).
+/// An empty path exports the whole document.
+///
+/// # Errors
+/// Returns [`TomlExtractError::MissingFields`] if any field without a
+/// default is missing, so that nothing partial is exported, and
+/// [`TomlExtractError::InvalidArgument`] if two fields map to the same
+/// variable name (`a-b` and `a_b` both become `A_B`).
+pub fn export_fields(
+    file_path: &str,
+    field_specs: &[String],
+    options: &ExportOptions,
+) -> Result<Vec<(String, String)>> {
+    let value = load_toml(file_path)?;
+    let mut leaves = Vec::new();
+    let mut missing = Vec::new();
+
+    for spec in field_specs {
+        let (field_path, default) = split_default(spec);
+        match (get_nested_value(&value, field_path), default) {
+            (Ok(field_value), _) => leaves.extend(flatten_leaves(field_path, field_value, options)),
+            (Err(e), Some(default)) if e.is_not_found() => leaves.extend(flatten_leaves(
+                field_path,
+                &TomlValue::String(default.to_string()),
+                options,
+            )),
+            (Err(e), None) if e.is_not_found() => missing.push(field_path.to_string()),
+            (Err(e), _) => return Err(e),
+        }
+    }
+
+    if !missing.is_empty() {
+        return Err(TomlExtractError::MissingFields(missing));
+    }
+
+    let mut vars: Vec<(String, String)> = Vec::with_capacity(leaves.len());
+    let mut sources: Vec<&str> = Vec::with_capacity(leaves.len());
+    for (source, name, value) in &leaves {
+        match vars.iter().position(|(seen, _)| seen == name) {
+            // The same field requested twice
+            Some(index) if sources[index] == source => {}
+            Some(index) => {
+                return Err(TomlExtractError::InvalidArgument(format!(
+                    "Fields `{}` and `{}` both export as {}",
+                    sources[index], source, name
+                )))
+            }
+            None => {
+                vars.push((name.clone(), value.clone()));
+                sources.push(source);
+            }
+        }
+    }
+    Ok(vars)
+}
+
+/// Flatten a value found at `path` into `(name, value)` pairs
+pub fn flatten_value(
+    path: &str,
+    value: &TomlValue,
+    options: &ExportOptions,
+) -> Vec<(String, String)> {
+    flatten_leaves(path, value, options)
+        .into_iter()
+        .map(|(_, name, value)| (name, value))
+        .collect()
+}
+
+/// Flatten a value into `(leaf path, name, value)` triples
+fn flatten_leaves(
+    path: &str,
+    value: &TomlValue,
+    options: &ExportOptions,
+) -> Vec<(String, String, String)> {
+    let mut segments = parse_path(path).unwrap_or_default();
+    let mut leaves = Vec::new();
+    flatten_into(&mut segments, value, options, &mut leaves);
+    leaves
+}
+
+fn flatten_into(
+    segments: &mut Vec<PathSegment>,
+    value: &TomlValue,
+    options: &ExportOptions,
+    leaves: &mut Vec<(String, String, String)>,
+) {
+    match value {
+        TomlValue::Table(table) => {
+            for (key, child) in table {
+                segments.push(PathSegment::Key(key.clone()));
+                flatten_into(segments, child, options, leaves);
+                segments.pop();
+            }
+        }
+        TomlValue::Array(array) => {
+            for (index, child) in array.iter().enumerate() {
+                segments.push(PathSegment::Index(index));
+                flatten_into(segments, child, options, leaves);
+                segments.pop();
+            }
+        }
+        _ => leaves.push((
+            format_path(segments),
+            variable_name(segments, options),
+            format_raw(value, ","),
+        )),
+    }
+}
+
+/// Build a variable name: mangle each segment to `[A-Za-z0-9_]`, join, prefix and apply case
+fn variable_name(segments: &[PathSegment], options: &ExportOptions) -> String {
+    let joined = segments
+        .iter()
+        .map(|segment| match segment {
+            PathSegment::Key(key) => key
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect::<String>(),
+            PathSegment::Index(index) => index.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(&options.separator);
+
+    let mut name = format!("{}{}", options.prefix, joined);
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+
+    match options.key_case {
+        KeyCase::Upper => name.to_uppercase(),
+        KeyCase::Lower => name.to_lowercase(),
+        KeyCase::Preserve => name,
+    }
+}
+
+/// Render variables in the given format
+pub fn render_export(vars: &[(String, String)], format: ExportFormat) -> String {
+    let mut out = String::new();
+    for (name, value) in vars {
+        match format {
+            ExportFormat::Env if is_plain(value) => writeln!(out, "{}={}", name, value),
+            ExportFormat::Env | ExportFormat::Dotenv => {
+                writeln!(out, "{}={}", name, dotenv_quote(value))
+            }
+            ExportFormat::Shell => writeln!(out, "export {}={}", name, shell_quote(value)),
+            ExportFormat::Gha => {
+                let delimiter = heredoc_delimiter(value);
+                writeln!(out, "{}<<{}\n{}\n{}", name, delimiter, value, delimiter)
+            }
+        }
+        .expect("writing to a String cannot fail");
+    }
+    out
+}
+
+/// Write variables to a `.env` file, replacing its contents
+pub fn write_dotenv(file_path: &str, vars: &[(String, String)]) -> Result<()> {
+    write_file(file_path, &render_export(vars, ExportFormat::Dotenv))
+}
+
+/// Append variables to the file named by `$GITHUB_OUTPUT`
+pub fn append_github_output(vars: &[(String, String)]) -> Result<()> {
+    let output_path = std::env::var("GITHUB_OUTPUT").map_err(|_| TomlExtractError::Io {
+        path: "$GITHUB_OUTPUT".to_string(),
+        source: std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "GITHUB_OUTPUT is not set (not running in GitHub Actions?)",
+        ),
+    })?;
+
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&output_path)
+        .and_then(|mut file| file.write_all(render_export(vars, ExportFormat::Gha).as_bytes()))
+        .map_err(|e| TomlExtractError::io(&output_path, e))
+}
+
+/// Single-quote a value for POSIX shells
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Whether a value reads the same with or without quotes
+fn is_plain(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-.,:/@+%".contains(c))
+}
+
+/// Quote a value for `.env` files
+///
+/// Single quotes keep the value literal (no interpolation); values containing
+/// a single quote or a newline fall back to double quotes, where `$` and
+/// backticks are escaped too so that loaders do not expand them.
+fn dotenv_quote(value: &str) -> String {
+    if !value.contains(['\'', '\n']) {
+        return format!("'{}'", value);
+    }
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('$', "\\$")
+        .replace('`', "\\`")
+        .replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
+
+/// Pick a heredoc delimiter that does not occur in `value`
+fn heredoc_delimiter(value: &str) -> String {
+    (0..)
+        .map(|n| format!("EOF_I_EDIT_TOML_{}", n))
+        .find(|delimiter| !value.contains(delimiter.as_str()))
+        .expect("an unused delimiter always exists")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> TomlValue {
+        toml::from_str(
+            r#"
+            [package]
+            name = "demo"
+            rust-version = "1.70"
+            authors = ["Ann O'Neil", "Bob"]
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_flatten_value_names() {
+        let value = sample();
+        let vars = flatten_value("package", &value["package"], &ExportOptions::default());
+        let names: Vec<_> = vars.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "PACKAGE_NAME",
+                "PACKAGE_RUST_VERSION",
+                "PACKAGE_AUTHORS_0",
+                "PACKAGE_AUTHORS_1"
+            ]
+        );
+
+        let options = ExportOptions {
+            prefix: "app__".to_string(),
+            separator: "__".to_string(),
+            key_case: KeyCase::Lower,
+        };
+        let vars = flatten_value(
+            "package.authors[1]",
+            &value["package"]["authors"][1],
+            &options,
+        );
+        assert_eq!(
+            vars,
+            [("app__package__authors__1".to_string(), "Bob".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_export_fields_name_collision() {
+        let mut temp_file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut temp_file, b"[a]\nx-y = 1\nx_y = 2\nz = 3\n").unwrap();
+        let path = temp_file.path().to_str().unwrap();
+        let options = ExportOptions::default();
+
+        let vars = export_fields(path, &["a.z".to_string(), "a.z".to_string()], &options).unwrap();
+        assert_eq!(vars, [("A_Z".to_string(), "3".to_string())]);
+
+        let err = export_fields(path, &["a".to_string()], &options).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            TomlExtractError::InvalidArgument(
+                "Fields `a.x-y` and `a.x_y` both export as A_X_Y".to_string()
+            )
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_render_export_quoting() {
+        let vars = vec![("AUTHOR".to_string(), "Ann O'Neil $HOME".to_string())];
+        assert_eq!(
+            render_export(&vars, ExportFormat::Shell),
+            "export AUTHOR='Ann O'\\''Neil $HOME'\n"
+        );
+        assert_eq!(
+            render_export(&vars, ExportFormat::Dotenv),
+            "AUTHOR=\"Ann O'Neil \\$HOME\"\n"
+        );
+        assert_eq!(
+            render_export(
+                &[("CMD".to_string(), "it's `date`\n".to_string())],
+                ExportFormat::Dotenv
+            ),
+            "CMD=\"it's \\`date\\`\\n\"\n"
+        );
+        assert_eq!(
+            render_export(
+                &[("NAME".to_string(), "$demo".to_string())],
+                ExportFormat::Dotenv
+            ),
+            "NAME='$demo'\n"
+        );
+        assert_eq!(
+            render_export(
+                &[
+                    ("VERSION".to_string(), "1.0.0-rc.1".to_string()),
+                    ("AUTHOR".to_string(), "Ann O'Neil $HOME".to_string()),
+                    ("EMPTY".to_string(), String::new()),
+                ],
+                ExportFormat::Env
+            ),
+            "VERSION=1.0.0-rc.1\nAUTHOR=\"Ann O'Neil \\$HOME\"\nEMPTY=''\n"
+        );
+        assert_eq!(
+            render_export(
+                &[("NOTE".to_string(), "a\nb".to_string())],
+                ExportFormat::Gha
+            ),
+            "NOTE<<EOF_I_EDIT_TOML_0\na\nb\nEOF_I_EDIT_TOML_0\n"
+        );
+    }
+}
@@ -4,8 +4,12 @@
 //! with support for nested structures, arrays, and Cargo.toml-specific convenience methods.
 
 pub mod core;
+pub mod export;
+pub mod template;
 pub mod types;
 pub mod utils;
+#[cfg(feature = "cli")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cli")))]
 pub mod xcli;
 
 // pub use core::*;
@@ -13,6 +17,9 @@ pub mod xcli;
 // pub use utils::strip_quotes;
 
 pub use core::*;
+pub use export::*;
+pub use template::*;
 pub use types::*;
 pub use utils::*;
+#[cfg(feature = "cli")]
 pub use xcli::*;
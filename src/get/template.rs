@@ -0,0 +1,329 @@
+//! Format-string output for get command
+//!
+//! A template interpolates field paths written in braces, e.g.
+//! `{package.name}@{package.version}`, with optional filters separated by `|`:
+//!
+//! | Filter | Effect |
+//! | ------ | ------ |
+//! | `upper`, `lower`, `trim` | Change case / strip surrounding whitespace |
+//! | `major`, `minor`, `patch` | Component of a `MAJOR.MINOR.PATCH` version |
+//! | `join(sep)` | Join array elements with `sep` (default `,`) |
+//! | `len` | Length of a string, array or table |
+//! | `default(x)` | Use `x` when the field is missing |
+//!
+//! Filter arguments may be quoted (`join(", ")`). Literal braces are written
+//! `{{` and `}}`.
+
+use toml::Value as TomlValue;
+
+use crate::document::load_toml;
+use crate::error::{Result, TomlExtractError};
+use crate::get::utils::{format_raw, get_nested_value, type_name};
+
+/// Names of the filters a placeholder may use
+const FILTERS: &[&str] = &[
+    "upper", "lower", "trim", "major", "minor", "patch", "join", "len", "default",
+];
+
+/// Render a template against a TOML file
+///
+/// Every placeholder is resolved against the same parsed document.
+pub fn extract_template(file_path: &str, template: &str) -> Result<String> {
+    let value = load_toml(file_path)?;
+    render_template(&value, template)
+}
+
+/// Render a template against a parsed TOML document
+///
+/// # Errors
+/// Returns [`TomlExtractError::InvalidTemplate`] for malformed templates or
+/// unknown filters, and the lookup error of a missing field that has no
+/// `default(...)` filter.
+pub fn render_template(value: &TomlValue, template: &str) -> Result<String> {
+    let mut out = String::new();
+    let mut chars = template.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '{' if chars.peek().map(|&(_, c)| c) == Some('{') => {
+                chars.next();
+                out.push('{');
+            }
+            '}' if chars.peek().map(|&(_, c)| c) == Some('}') => {
+                chars.next();
+                out.push('}');
+            }
+            '{' => {
+                let end = find_placeholder_end(template, i + 1).ok_or_else(|| {
+                    TomlExtractError::InvalidTemplate(format!("Unclosed '{{' at offset {}", i))
+                })?;
+                out.push_str(&render_placeholder(value, &template[i + 1..end])?);
+                while chars.peek().is_some_and(|&(j, _)| j <= end) {
+                    chars.next();
+                }
+            }
+            '}' => {
+                return Err(TomlExtractError::InvalidTemplate(format!(
+                    "Unmatched '}}' at offset {}",
+                    i
+                )))
+            }
+            _ => out.push(c),
+        }
+    }
+
+    Ok(out)
+}
+
+/// Find the `}` closing a placeholder that starts at `start`, skipping quoted filter arguments
+fn find_placeholder_end(template: &str, start: usize) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in template[start..].char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '}') => return Some(start + i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn render_placeholder(root: &TomlValue, placeholder: &str) -> Result<String> {
+    let mut parts = split_filters(placeholder).into_iter();
+    let path = parts.next().unwrap_or_default().trim().to_string();
+    if path.is_empty() {
+        return Err(TomlExtractError::InvalidTemplate(format!(
+            "Empty field path in '{{{}}}'",
+            placeholder
+        )));
+    }
+    // Check the whole chain first, so a bad filter is reported even when the
+    // field is missing
+    let filters = parts
+        .map(|filter| parse_filter(&filter))
+        .collect::<Result<Vec<_>>>()?;
+
+    // A missing field is only an error if no `default(...)` filter supplies a value
+    let mut lookup_error = None;
+    let mut current = match get_nested_value(root, &path) {
+        Ok(value) => Some(value.clone()),
+        Err(e) if e.is_not_found() => {
+            lookup_error = Some(e);
+            None
+        }
+        Err(e) => return Err(e),
+    };
+
+    for (name, arg) in filters {
+        current = match (name.as_str(), current) {
+            ("default", None) => Some(TomlValue::String(arg.unwrap_or_default())),
+            ("default", Some(value)) => Some(value),
+            (_, None) => None,
+            (_, Some(value)) => Some(apply_filter(&path, &name, arg.as_deref(), value)?),
+        };
+    }
+
+    match current {
+        Some(TomlValue::Array(array)) if array.iter().all(is_scalar) => {
+            Ok(TomlValue::Array(array).to_string())
+        }
+        Some(value) => Ok(format_raw(&value, ",")),
+        None => Err(lookup_error.expect("missing value always records its lookup error")),
+    }
+}
+
+/// Split `path|filter|filter(arg)` on `|` outside quotes
+fn split_filters(placeholder: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+
+    for c in placeholder.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => {
+                quote = None;
+                current.push(c);
+            }
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                current.push(c);
+            }
+            (None, '|') => parts.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    parts.push(current);
+
+    parts
+}
+
+/// Parse `name` or `name(arg)`, unquoting the argument if quoted
+fn parse_filter(filter: &str) -> Result<(String, Option<String>)> {
+    let filter = filter.trim();
+    let name = filter.split('(').next().unwrap_or_default().trim();
+    if !FILTERS.contains(&name) {
+        return Err(TomlExtractError::InvalidTemplate(format!(
+            "Unknown filter '{}'",
+            name
+        )));
+    }
+    let Some(open) = filter.find('(') else {
+        return Ok((name.to_string(), None));
+    };
+    if !filter.ends_with(')') {
+        return Err(TomlExtractError::InvalidTemplate(format!(
+            "Unclosed '(' in filter '{}'",
+            filter
+        )));
+    }
+
+    let arg = filter[open + 1..filter.len() - 1].trim();
+    let arg = match arg.chars().next() {
+        Some(q @ ('"' | '\'')) if arg.len() >= 2 && arg.ends_with(q) => &arg[1..arg.len() - 1],
+        _ => arg,
+    };
+
+    Ok((name.to_string(), Some(arg.to_string())))
+}
+
+fn apply_filter(path: &str, name: &str, arg: Option<&str>, value: TomlValue) -> Result<TomlValue> {
+    let text = || format_raw(&value, ",");
+
+    match name {
+        "upper" => Ok(TomlValue::String(text().to_uppercase())),
+        "lower" => Ok(TomlValue::String(text().to_lowercase())),
+        "trim" => Ok(TomlValue::String(text().trim().to_string())),
+        "major" | "minor" | "patch" => {
+            let position = ["major", "minor", "patch"]
+                .iter()
+                .position(|n| *n == name)
+                .unwrap();
+            version_component(&text(), position)
+                .map(TomlValue::String)
+                .ok_or_else(|| TomlExtractError::TypeMismatch {
+                    path: path.to_string(),
+                    expected: "version (MAJOR.MINOR.PATCH)".to_string(),
+                    found: text(),
+                })
+        }
+        "join" => match &value {
+            TomlValue::Array(array) => Ok(TomlValue::String(
+                array
+                    .iter()
+                    .map(|elem| format_raw(elem, ","))
+                    .collect::<Vec<_>>()
+                    .join(arg.unwrap_or(",")),
+            )),
+            other => Err(type_mismatch(path, "array", other)),
+        },
+        "len" => match &value {
+            TomlValue::Array(array) => Ok(TomlValue::Integer(array.len() as i64)),
+            TomlValue::Table(table) => Ok(TomlValue::Integer(table.len() as i64)),
+            TomlValue::String(s) => Ok(TomlValue::Integer(s.chars().count() as i64)),
+            other => Err(type_mismatch(path, "table, array or string", other)),
+        },
+        other => unreachable!("filter '{}' is rejected by parse_filter", other),
+    }
+}
+
+/// Get a numeric component of a version string such as `v1.2.3-beta.1`
+fn version_component(version: &str, position: usize) -> Option<String> {
+    let core = version
+        .trim()
+        .trim_start_matches('v')
+        .split(['-', '+'])
+        .next()?;
+    let component = core.split('.').nth(position)?;
+    component
+        .chars()
+        .all(|c| c.is_ascii_digit())
+        .then(|| component.to_string())
+        .filter(|c| !c.is_empty())
+}
+
+fn is_scalar(value: &TomlValue) -> bool {
+    !matches!(value, TomlValue::Array(_) | TomlValue::Table(_))
+}
+
+fn type_mismatch(path: &str, expected: &str, found: &TomlValue) -> TomlExtractError {
+    TomlExtractError::TypeMismatch {
+        path: path.to_string(),
+        expected: expected.to_string(),
+        found: type_name(found).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> TomlValue {
+        toml::from_str(
+            r#"
+            [package]
+            name = "demo"
+            version = "1.12.3-rc.1"
+            authors = ["Alice", "Bob"]
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_render_template_basic() {
+        let value = sample();
+        assert_eq!(
+            render_template(&value, "{package.name}@{package.version}").unwrap(),
+            "demo@1.12.3-rc.1"
+        );
+        assert_eq!(
+            render_template(&value, "{{literal}} {package.name|upper}").unwrap(),
+            "{literal} DEMO"
+        );
+    }
+
+    #[test]
+    fn test_render_template_filters() {
+        let value = sample();
+        assert_eq!(
+            render_template(
+                &value,
+                "v{package.version|major}.{package.version|minor} by {package.authors|join(\", \")}"
+            )
+            .unwrap(),
+            "v1.12 by Alice, Bob"
+        );
+        assert_eq!(
+            render_template(&value, "{package.license|default(MIT)|lower}").unwrap(),
+            "mit"
+        );
+        assert_eq!(
+            render_template(&value, "{package.authors|len}").unwrap(),
+            "2"
+        );
+    }
+
+    #[test]
+    fn test_render_template_errors() {
+        let value = sample();
+        assert!(matches!(
+            render_template(&value, "{package.license}"),
+            Err(TomlExtractError::FieldNotFound { .. })
+        ));
+        assert!(matches!(
+            render_template(&value, "{package.name|shout}"),
+            Err(TomlExtractError::InvalidTemplate(_))
+        ));
+        // The filter chain is checked before the field is looked up
+        let err = render_template(&value, "{package.license|default(MIT)|shout}").unwrap_err();
+        assert_eq!(err.to_string(), "Invalid template: Unknown filter 'shout'");
+        let err = render_template(&value, "{missing|shout}").unwrap_err();
+        assert_eq!(err.to_string(), "Invalid template: Unknown filter 'shout'");
+        assert!(matches!(
+            render_template(&value, "{package.name"),
+            Err(TomlExtractError::InvalidTemplate(_))
+        ));
+    }
+}
@@ -1,6 +1,7 @@
 //! Type definitions for get command
 
-use std::collections::HashMap;
+#[cfg(feature = "json")]
+use serde_json::{Map as JsonMap, Value as JsonValue};
 
 /// Configuration for field extraction
 #[derive(Debug, Clone)]
@@ -13,6 +14,8 @@ pub struct ExtractConfig {
     pub output_format: Option<String>,
     /// Whether to strip quotes from string values
     pub strip_quotes: bool,
+    /// Fallback returned verbatim when the field is missing (None to fail instead)
+    pub default: Option<String>,
 }
 
 impl Default for ExtractConfig {
@@ -22,35 +25,50 @@ impl Default for ExtractConfig {
             field_path: "package.name".to_string(),
             output_format: None,
             strip_quotes: false,
+            default: None,
         }
     }
 }
 
 /// Result of multiple field extraction
+///
+/// Fields keep the order in which they were requested and hold typed JSON
+/// values, so tables and arrays nest as real JSON rather than TOML text.
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
 #[derive(Debug, Clone)]
 pub struct ExtractionResult {
-    /// Extracted fields and their values
-    pub fields: HashMap<String, String>,
+    /// Extracted fields and their values, in request order
+    pub fields: JsonMap<String, JsonValue>,
+    /// Requested fields that were missing and had no default
+    pub missing: Vec<String>,
     /// Path to the source TOML file
     pub source_file: String,
 }
 
+#[cfg(feature = "json")]
 impl ExtractionResult {
     /// Create a new extraction result
     pub fn new(source_file: String) -> Self {
         Self {
-            fields: HashMap::new(),
+            fields: JsonMap::new(),
+            missing: Vec::new(),
             source_file,
         }
     }
 
     /// Add a field to the result
-    pub fn add_field(&mut self, field_path: String, value: String) {
+    pub fn add_field(&mut self, field_path: String, value: JsonValue) {
         self.fields.insert(field_path, value);
     }
 
+    /// Record a field that was missing and had no default
+    pub fn add_missing(&mut self, field_path: String) {
+        self.missing.push(field_path);
+    }
+
     /// Get a field value
-    pub fn get(&self, field_path: &str) -> Option<&String> {
+    pub fn get(&self, field_path: &str) -> Option<&JsonValue> {
         self.fields.get(field_path)
     }
 
@@ -59,13 +77,52 @@ impl ExtractionResult {
         self.fields.is_empty()
     }
 
+    /// Check if every requested field was resolved (found or defaulted)
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+
     /// Convert to JSON string
+    ///
+    /// The output is an object with the extracted values under `fields` and
+    /// the paths that could not be resolved under `missing`.
     pub fn to_json(&self) -> Result<String, serde_json::Error> {
-        serde_json::to_string(&self.fields)
+        serde_json::to_string(&self.to_json_value())
     }
 
     /// Convert to pretty JSON string
     pub fn to_json_pretty(&self) -> Result<String, serde_json::Error> {
-        serde_json::to_string_pretty(&self.fields)
+        serde_json::to_string_pretty(&self.to_json_value())
+    }
+
+    /// Convert to a YAML document with the same shape as [`Self::to_json`]
+    #[cfg(feature = "yaml")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "yaml")))]
+    pub fn to_yaml(&self) -> Result<String, serde_yaml::Error> {
+        serde_yaml::to_string(&self.to_json_value())
+    }
+
+    /// One NUL-terminated record per path in `field_paths`, in that order
+    ///
+    /// Strings are written raw and other values as JSON; a missing field gets
+    /// an empty record, so record `n` always belongs to the `n`-th path.
+    pub fn to_nul(&self, field_paths: &[&str]) -> String {
+        let mut out = String::new();
+        for path in field_paths {
+            match self.fields.get(*path) {
+                Some(JsonValue::String(s)) => out.push_str(s),
+                Some(value) => out.push_str(&value.to_string()),
+                None => {}
+            }
+            out.push('\0');
+        }
+        out
+    }
+
+    fn to_json_value(&self) -> JsonValue {
+        serde_json::json!({
+            "fields": self.fields,
+            "missing": self.missing,
+        })
     }
 }
@@ -1,99 +1,305 @@
 //! Utility functions for get command
 
-use anyhow::Result;
+#[cfg(feature = "json")]
 use serde_json::Value as JsonValue;
 use toml::Value as TomlValue;
 
-use crate::error::TomlExtractError;
+use crate::error::{Result, TomlExtractError};
+use crate::path::{format_path, parse_path, PathSegment};
 
-/// Resolve nested value from TOML structure using dot-separated path with array support
+/// Resolve nested value from TOML structure using a field path
+///
+/// The path syntax is described in [`crate::path`]. An empty path resolves to
+/// the whole document. Missing keys produce a
+/// [`TomlExtractError::FieldNotFound`] carrying the full path, the deepest
+/// existing prefix, the keys available there and a closest-match suggestion.
+/// A key below a string, number, boolean or datetime is a
+/// [`TomlExtractError::TypeMismatch`] naming that value's type.
 pub fn get_nested_value<'a>(
     value: &'a TomlValue,
     path: &str,
 ) -> Result<&'a TomlValue, TomlExtractError> {
+    let segments = parse_path(path)?;
+    resolve_segments(value, path, &segments)
+}
+
+/// Resolve already parsed path segments, with the same errors as [`get_nested_value`]
+pub fn get_value_at<'a>(value: &'a TomlValue, segments: &[PathSegment]) -> Result<&'a TomlValue> {
+    resolve_segments(value, &format_path(segments), segments)
+}
+
+fn resolve_segments<'a>(
+    value: &'a TomlValue,
+    path: &str,
+    segments: &[PathSegment],
+) -> Result<&'a TomlValue> {
     let mut current = value;
 
-    for part in path.split('.') {
-        // Handle array access syntax [index]
-        if part.contains('[') && part.ends_with(']') {
-            let bracket_start = part.find('[').ok_or_else(|| {
-                TomlExtractError::InvalidArrayIndex(format!("Invalid array syntax: {}", part))
-            })?;
-            let array_name = &part[..bracket_start];
-            let index_str = &part[bracket_start + 1..part.len() - 1];
-            let index = index_str.parse::<usize>().map_err(|_| {
-                TomlExtractError::InvalidArrayIndex(format!("Invalid array index: {}", index_str))
-            })?;
-
-            // Get array from current value
-            current = current
-                .get(array_name)
-                .ok_or_else(|| TomlExtractError::FieldNotFound(array_name.to_string()))?;
-            let array = current
-                .as_array()
-                .ok_or_else(|| TomlExtractError::NotAnArray(array_name.to_string()))?;
-
-            // Check index bounds
-            if index >= array.len() {
-                return Err(TomlExtractError::ArrayIndexOutOfBounds {
-                    path: array_name.to_string(),
-                    index,
-                    length: array.len(),
-                });
+    for (i, segment) in segments.iter().enumerate() {
+        current = match segment {
+            PathSegment::Key(_) if !current.is_table() => {
+                return Err(TomlExtractError::TypeMismatch {
+                    path: format_path(&segments[..i]),
+                    expected: "table".to_string(),
+                    found: type_name(current).to_string(),
+                })
+            }
+            PathSegment::Key(key) => current
+                .get(key.as_str())
+                .ok_or_else(|| field_not_found(path, segments, i, current))?,
+            PathSegment::Index(index) => {
+                let array_path = format_path(&segments[..i]);
+                let array = current
+                    .as_array()
+                    .ok_or_else(|| TomlExtractError::NotAnArray(array_path.clone()))?;
+                array
+                    .get(*index)
+                    .ok_or(TomlExtractError::ArrayIndexOutOfBounds {
+                        path: array_path,
+                        index: *index,
+                        length: array.len(),
+                    })?
             }
+        };
+    }
+
+    Ok(current)
+}
 
-            current = &array[index];
-        } else {
-            // Regular field access
-            current = current
-                .get(part)
-                .ok_or_else(|| TomlExtractError::FieldNotFound(part.to_string()))?;
+/// Build a `FieldNotFound` error for the key `segments[at]`, missing from `parent`
+///
+/// When the rest of the path also resolves under the suggested key, the
+/// suggestion is the whole corrected path rather than just the corrected
+/// prefix.
+fn field_not_found(
+    path: &str,
+    segments: &[PathSegment],
+    at: usize,
+    parent: &TomlValue,
+) -> TomlExtractError {
+    let missing = match &segments[at] {
+        PathSegment::Key(key) => key.as_str(),
+        PathSegment::Index(_) => unreachable!("only keys can be missing"),
+    };
+    let available: Vec<String> = parent
+        .as_table()
+        .map(|table| table.keys().cloned().collect())
+        .unwrap_or_default();
+    let suggestion = closest_match(missing, available.iter().map(String::as_str)).map(|key| {
+        let mut corrected = segments.to_vec();
+        corrected[at] = PathSegment::Key(key.to_string());
+        if resolve_segments(parent, path, &corrected[at..]).is_err() {
+            corrected.truncate(at + 1);
         }
+        format_path(&corrected)
+    });
+
+    TomlExtractError::FieldNotFound {
+        path: path.to_string(),
+        missing: missing.to_string(),
+        found: format_path(&segments[..at]),
+        available,
+        suggestion,
     }
+}
 
-    Ok(current)
+/// Find the candidate closest to `target` by edit distance, if any is close enough
+///
+/// A candidate qualifies when its edit distance is at most a third of
+/// the target's length (and at least 1).
+pub fn closest_match<'a>(
+    target: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let threshold = (target.chars().count() / 3).max(1);
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(target, candidate), candidate))
+        .filter(|(distance, _)| *distance <= threshold)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Edit distance between two strings, counted in characters
+///
+/// Uses the optimal string alignment variant of Levenshtein distance, so an
+/// adjacent transposition (`verison` → `version`) costs a single edit.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    d[a.len()][b.len()]
 }
 
 /// Format TOML value for output based on specified format
+///
+/// Supported formats are `raw` (TOML representation, the default), `lines`
+/// and `nul` (see [`format_raw`]), `toml` (a table as a standalone document),
+/// with the `json` feature `json` and `json-pretty`, and with the `yaml`
+/// feature `yaml`.
 pub fn format_output(value: &TomlValue, output_format: Option<&str>) -> Result<String> {
     match output_format {
+        Some("toml") => match value {
+            TomlValue::Table(_) => Ok(toml::to_string_pretty(value)?.trim_end().to_string()),
+            _ => Ok(value.to_string()),
+        },
+        #[cfg(feature = "yaml")]
+        Some("yaml") => {
+            let json_value = to_json_value(value)?;
+            Ok(serde_yaml::to_string(&json_value)?.trim_end().to_string())
+        }
+        #[cfg(not(feature = "yaml"))]
+        Some("yaml") => Err(TomlExtractError::InvalidArgument(
+            "Output format 'yaml' requires the `yaml` feature".to_string(),
+        )),
+        #[cfg(feature = "json")]
         Some("json") => {
             let json_value = to_json_value(value)?;
             Ok(serde_json::to_string(&json_value)?)
         }
+        #[cfg(feature = "json")]
         Some("json-pretty") => {
             let json_value = to_json_value(value)?;
             Ok(serde_json::to_string_pretty(&json_value)?)
         }
+        Some("lines") => Ok(format_raw(value, "\n")),
+        Some("nul") => Ok(format_raw(value, "\0")),
+        #[cfg(not(feature = "json"))]
+        Some(format @ ("json" | "json-pretty")) => Err(TomlExtractError::InvalidArgument(format!(
+            "Output format '{}' requires the `json` feature",
+            format
+        ))),
         _ => Ok(value.to_string()),
     }
 }
 
+/// Render the value found at `path` as a standalone TOML document
+///
+/// The value is nested back under its full path, so a table keeps its
+/// `[header]` and a scalar becomes `key = value` under its parent's header;
+/// the output can be pasted back into a document. Indexed segments
+/// (`bin[1]`) become a single-element array of tables.
+pub fn format_toml_fragment(path: &str, value: &TomlValue) -> Result<String> {
+    let mut wrapped = value.clone();
+    for segment in parse_path(path)?.into_iter().rev() {
+        wrapped = match segment {
+            PathSegment::Index(_) => TomlValue::Array(vec![wrapped]),
+            PathSegment::Key(key) => {
+                let mut table = toml::Table::new();
+                table.insert(key, wrapped);
+                TomlValue::Table(table)
+            }
+        };
+    }
+
+    match wrapped {
+        TomlValue::Table(_) => Ok(toml::to_string_pretty(&wrapped)?.trim_end().to_string()),
+        other => Err(TomlExtractError::TypeMismatch {
+            path: path.to_string(),
+            expected: "table (an empty path must refer to the document root)".to_string(),
+            found: type_name(&other).to_string(),
+        }),
+    }
+}
+
+/// Format a value for shell consumption
+///
+/// Strings are printed as their decoded content, numbers, booleans and
+/// datetimes bare, and array elements are formatted the same way and joined
+/// with `separator`. Tables, and arrays or tables nested inside arrays, keep
+/// their TOML representation.
+pub fn format_raw(value: &TomlValue, separator: &str) -> String {
+    match value {
+        TomlValue::String(s) => s.clone(),
+        TomlValue::Array(array) => array
+            .iter()
+            .map(|elem| match elem {
+                TomlValue::Array(_) | TomlValue::Table(_) => elem.to_string(),
+                _ => format_raw(elem, separator),
+            })
+            .collect::<Vec<_>>()
+            .join(separator),
+        _ => value.to_string(),
+    }
+}
+
 /// Convert TomlValue to serde_json::Value
+///
+/// Datetimes become plain strings; see [`to_json_value_with`] to choose
+/// another representation.
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
 pub fn to_json_value(toml_value: &TomlValue) -> Result<JsonValue> {
+    to_json_value_with(toml_value, &|dt| JsonValue::String(dt.to_string()))
+}
+
+/// Convert TomlValue to serde_json::Value, rendering datetimes with `datetime`
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub fn to_json_value_with(
+    toml_value: &TomlValue,
+    datetime: &dyn Fn(&toml::value::Datetime) -> JsonValue,
+) -> Result<JsonValue> {
     match toml_value {
         TomlValue::String(s) => Ok(JsonValue::String(s.clone())),
         TomlValue::Integer(i) => Ok(JsonValue::Number((*i).into())),
         TomlValue::Float(f) => Ok(JsonValue::Number(
-            serde_json::Number::from_f64(*f)
-                .ok_or_else(|| anyhow::anyhow!("Cannot convert float {} to JSON number", f))?,
+            serde_json::Number::from_f64(*f).ok_or_else(|| {
+                TomlExtractError::InvalidValueType(format!(
+                    "Cannot convert float {} to JSON number",
+                    f
+                ))
+            })?,
         )),
         TomlValue::Boolean(b) => Ok(JsonValue::Bool(*b)),
         TomlValue::Array(arr) => {
             let mut json_arr = Vec::with_capacity(arr.len());
             for elem in arr {
-                json_arr.push(to_json_value(elem)?);
+                json_arr.push(to_json_value_with(elem, datetime)?);
             }
             Ok(JsonValue::Array(json_arr))
         }
         TomlValue::Table(table) => {
             let mut json_obj = serde_json::Map::new();
             for (key, val) in table {
-                json_obj.insert(key.clone(), to_json_value(val)?);
+                json_obj.insert(key.clone(), to_json_value_with(val, datetime)?);
             }
             Ok(JsonValue::Object(json_obj))
         }
-        TomlValue::Datetime(dt) => Ok(JsonValue::String(dt.to_string())),
+        TomlValue::Datetime(dt) => Ok(datetime(dt)),
+    }
+}
+
+/// Name of the TOML type of a value (string, integer, float, boolean, datetime, array, table)
+pub fn type_name(value: &TomlValue) -> &'static str {
+    match value {
+        TomlValue::String(_) => "string",
+        TomlValue::Integer(_) => "integer",
+        TomlValue::Float(_) => "float",
+        TomlValue::Boolean(_) => "boolean",
+        TomlValue::Datetime(_) => "datetime",
+        TomlValue::Array(_) => "array",
+        TomlValue::Table(_) => "table",
     }
 }
 
@@ -148,6 +354,113 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_get_nested_value_not_found_context() {
+        let value: TomlValue =
+            toml::from_str("[package]\nname = \"x\"\nversion = \"1.0\"\nauthors = [\"a\"]")
+                .unwrap();
+
+        match get_nested_value(&value, "package.verison").unwrap_err() {
+            TomlExtractError::FieldNotFound {
+                path,
+                missing,
+                found,
+                available,
+                suggestion,
+            } => {
+                assert_eq!(path, "package.verison");
+                assert_eq!(missing, "verison");
+                assert_eq!(found, "package");
+                assert_eq!(available.len(), 3);
+                assert_eq!(suggestion.as_deref(), Some("package.version"));
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+
+        let err = get_nested_value(&value, "package.authors[2]").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Array index out of bounds: package.authors[2], array length: 1, valid indices: 0..=0"
+        );
+
+        let err = get_nested_value(&value, "package.name.x").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Type mismatch at package.name: expected table, found string"
+        );
+        assert_eq!(err.exit_code(), 4);
+
+        let err = get_nested_value(&value, "package.authors.name").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Type mismatch at package.authors: expected table, found array"
+        );
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("version", "verison"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(closest_match("nmae", ["name", "version"]), Some("name"));
+        assert_eq!(closest_match("zzz", ["name", "version"]), None);
+    }
+
+    #[test]
+    fn test_format_raw() {
+        let value: TomlValue = toml::from_str(
+            r#"
+            quoted = "say \"hi\" \u00e9"
+            number = 42
+            flag = true
+            list = ["a b", "c"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(format_raw(&value["quoted"], "\n"), "say \"hi\" é");
+        assert_eq!(format_raw(&value["number"], "\n"), "42");
+        assert_eq!(format_raw(&value["flag"], "\n"), "true");
+        assert_eq!(format_raw(&value["list"], "\n"), "a b\nc");
+        assert_eq!(
+            format_output(&value["list"], Some("nul")).unwrap(),
+            "a b\0c"
+        );
+    }
+
+    #[test]
+    fn test_format_toml_fragment() {
+        let value: TomlValue = toml::from_str(
+            r#"
+            [package]
+            name = "demo"
+            [profile.release]
+            opt-level = 3
+            lto = true
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            format_toml_fragment("package.name", &value["package"]["name"]).unwrap(),
+            "[package]\nname = \"demo\""
+        );
+        assert_eq!(
+            format_toml_fragment("profile.release", &value["profile"]["release"]).unwrap(),
+            "[profile.release]\nopt-level = 3\nlto = true"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "yaml")]
+    fn test_format_output_yaml() {
+        let value: TomlValue = toml::from_str("name = \"demo\"\nlist = [1, 2]").unwrap();
+        assert_eq!(
+            format_output(&value, Some("yaml")).unwrap(),
+            "name: demo\nlist:\n- 1\n- 2"
+        );
+    }
+
     #[test]
     fn test_strip_quotes_internal() {
         assert_eq!(strip_quotes_internal("\"hello\""), "hello");
@@ -156,6 +469,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "json")]
     fn test_to_json_value() {
         let toml_value = TomlValue::Integer(42);
         assert_eq!(
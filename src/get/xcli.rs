@@ -1,10 +1,14 @@
 //! CLI definitions and handling for get command
 
-use anyhow::Result;
 use clap::{Arg, Command};
 
 use super::core::*;
+use super::export::{
+    append_github_output, export_fields, render_export, write_dotenv, ExportFormat, ExportOptions,
+};
+use super::template::extract_template;
 use super::types::ExtractConfig;
+use crate::error::{Result, TomlExtractError};
 
 /// Define the get command CLI structure
 pub fn get_command() -> Command {
@@ -25,7 +29,7 @@ pub fn get_command() -> Command {
                 .value_name("FIELD")
                 .help("Dot-separated field path (e.g., package.name, authors[0], bin[1].name)")
                 .required_unless_present_any([
-                    "multiple", "package-name", "package-version", "dependencies",
+                    "multiple", "format", "package-name", "package-version", "dependencies",
                     "authors", "keywords", "categories", "array", "array-length", "array-element"
                 ]),
         )
@@ -35,16 +39,71 @@ pub fn get_command() -> Command {
                 .long("multiple")
                 .value_name("FIELDS")
                 .action(clap::ArgAction::Append)
-                .help("Extract multiple fields (can be used multiple times)"),
+                .help("Extract multiple fields (can be used multiple times); append =DEFAULT for a fallback, e.g. package.license=UNLICENSED"),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_name("TEMPLATE")
+                .help("Render a template such as '{package.name}@{package.version}'; filters: upper, lower, trim, major, minor, patch, join(sep), len, default(x)"),
+        )
+        .arg(
+            Arg::new("default")
+                .long("default")
+                .value_name("VALUE")
+                .help("Use the string VALUE, formatted like a found value, when the field given with --field is missing"),
         )
         .arg(
             Arg::new("output")
                 .short('o')
                 .long("output")
                 .value_name("FORMAT")
-                .help("Output format (raw, json, json-pretty)")
+                .help("Output format (raw, lines, nul, json, json-pretty, yaml, toml, env, shell, dotenv, gha)")
                 .default_value("raw"),
         )
+        .arg(
+            Arg::new("env-prefix")
+                .long("env-prefix")
+                .value_name("PREFIX")
+                .help("Prefix for variable names with env, shell, dotenv and gha output")
+                .default_value(""),
+        )
+        .arg(
+            Arg::new("key-case")
+                .long("key-case")
+                .value_name("CASE")
+                .help("Case of variable names (upper, lower, preserve)")
+                .value_parser(["upper", "lower", "preserve"])
+                .default_value("upper"),
+        )
+        .arg(
+            Arg::new("flatten-separator")
+                .long("flatten-separator")
+                .value_name("SEP")
+                .help("Separator between path segments of flattened variable names")
+                .default_value("_"),
+        )
+        .arg(
+            Arg::new("output-file")
+                .long("output-file")
+                .value_name("FILE")
+                .help("File written by --output dotenv")
+                .default_value(".env"),
+        )
+        .arg(
+            Arg::new("raw-output")
+                .short('r')
+                .long("raw")
+                .help("Print decoded strings and bare scalars, arrays one element per line (same as -o lines)")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("null")
+                .short('0')
+                .long("null")
+                .help("Like --raw, but separate and terminate array elements with NUL (same as -o nul); with -m, one value per field")
+                .action(clap::ArgAction::SetTrue),
+        )
         .arg(
             Arg::new("strip-quotes")
                 .long("strip-quotes")
@@ -119,50 +178,50 @@ pub fn get_command() -> Command {
             Arg::new("quiet")
                 .short('q')
                 .long("quiet")
-                .help("Suppress error messages for missing fields")
+                .help("Suppress error messages (the exit status still reports failures)")
                 .action(clap::ArgAction::SetTrue),
         )
 }
 
 /// Handle get command logic
+///
+/// Failures are returned rather than printed, so the caller can report them
+/// (unless `--quiet`) and map them to an exit code with
+/// [`TomlExtractError::exit_code`](crate::TomlExtractError::exit_code).
 pub fn handle_get_command(matches: &clap::ArgMatches) -> Result<()> {
     let file_path = matches.get_one::<String>("file").unwrap();
-    let output_format = matches.get_one::<String>("output").unwrap();
+    let output_format = if matches.get_flag("null") {
+        "nul"
+    } else if matches.get_flag("raw-output") {
+        "lines"
+    } else {
+        matches.get_one::<String>("output").unwrap().as_str()
+    };
     let strip_quotes = matches.get_flag("strip-quotes");
-    let quiet = matches.get_flag("quiet");
 
     // Handle array operations
     if let Some(array_path) = matches.get_one::<String>("array") {
-        match extract_array(file_path, array_path, Some(output_format)) {
-            Ok(result) => println!("{}", result),
-            Err(e) if !quiet => eprintln!("Error: {}", e),
-            _ => {}
-        }
+        print_output(
+            &extract_array(file_path, array_path, Some(output_format))?,
+            output_format,
+        );
         return Ok(());
     }
 
     if let Some(array_path) = matches.get_one::<String>("array-length") {
-        match extract_array_length(file_path, array_path) {
-            Ok(length) => println!("{}", length),
-            Err(e) if !quiet => eprintln!("Error: {}", e),
-            _ => {}
-        }
+        println!("{}", extract_array_length(file_path, array_path)?);
         return Ok(());
     }
 
     if let Some(array_path) = matches.get_one::<String>("array-element") {
         if let Some(index_str) = matches.get_one::<String>("array-index") {
-            match index_str.parse::<usize>() {
-                Ok(index) => {
-                    match extract_array_element(file_path, array_path, index, strip_quotes) {
-                        Ok(element) => println!("{}", element),
-                        Err(e) if !quiet => eprintln!("Error: {}", e),
-                        _ => {}
-                    }
-                }
-                Err(_) if !quiet => eprintln!("Error: Invalid array index: {}", index_str),
-                _ => {}
-            }
+            let index = index_str
+                .parse::<usize>()
+                .map_err(|_| TomlExtractError::InvalidArrayIndex(index_str.to_string()))?;
+            println!(
+                "{}",
+                extract_array_element(file_path, array_path, index, strip_quotes)?
+            );
         }
         return Ok(());
     }
@@ -173,11 +232,10 @@ pub fn handle_get_command(matches: &clap::ArgMatches) -> Result<()> {
             .get_one::<String>("authors")
             .and_then(|s| s.parse::<usize>().ok());
 
-        match get_package_authors(Some(file_path), index, strip_quotes) {
-            Ok(authors) => println!("{}", authors),
-            Err(e) if !quiet => eprintln!("Error: {}", e),
-            _ => {}
-        }
+        println!(
+            "{}",
+            get_package_authors(Some(file_path), index, strip_quotes)?
+        );
         return Ok(());
     }
 
@@ -186,11 +244,10 @@ pub fn handle_get_command(matches: &clap::ArgMatches) -> Result<()> {
             .get_one::<String>("keywords")
             .and_then(|s| s.parse::<usize>().ok());
 
-        match get_package_keywords(Some(file_path), index, strip_quotes) {
-            Ok(keywords) => println!("{}", keywords),
-            Err(e) if !quiet => eprintln!("Error: {}", e),
-            _ => {}
-        }
+        println!(
+            "{}",
+            get_package_keywords(Some(file_path), index, strip_quotes)?
+        );
         return Ok(());
     }
 
@@ -199,62 +256,104 @@ pub fn handle_get_command(matches: &clap::ArgMatches) -> Result<()> {
             .get_one::<String>("categories")
             .and_then(|s| s.parse::<usize>().ok());
 
-        match get_package_categories(Some(file_path), index, strip_quotes) {
-            Ok(categories) => println!("{}", categories),
-            Err(e) if !quiet => eprintln!("Error: {}", e),
-            _ => {}
-        }
+        println!(
+            "{}",
+            get_package_categories(Some(file_path), index, strip_quotes)?
+        );
         return Ok(());
     }
 
     // Handle convenience flags
     if matches.get_flag("package-name") {
-        match get_package_name(Some(file_path)) {
-            Ok(name) => println!("{}", name),
-            Err(e) if !quiet => eprintln!("Error: {}", e),
-            _ => {}
-        }
+        println!("{}", get_package_name(Some(file_path))?);
         return Ok(());
     }
 
     if matches.get_flag("package-version") {
-        match get_package_version(Some(file_path)) {
-            Ok(version) => println!("{}", version),
-            Err(e) if !quiet => eprintln!("Error: {}", e),
-            _ => {}
-        }
+        println!("{}", get_package_version(Some(file_path))?);
         return Ok(());
     }
 
     if matches.get_flag("dependencies") {
-        match get_dependencies(Some(file_path)) {
-            Ok(deps) => {
-                let json = serde_json::to_string_pretty(&deps)?;
-                println!("{}", json);
+        let deps = get_dependencies(Some(file_path))?;
+        println!("{}", serde_json::to_string_pretty(&deps)?);
+        return Ok(());
+    }
+
+    if let Some(template) = matches.get_one::<String>("format") {
+        println!("{}", extract_template(file_path, template)?);
+        return Ok(());
+    }
+
+    // Handle environment-style output
+    let export_specs: Option<Vec<String>> = match (
+        matches.get_many::<String>("multiple"),
+        matches.get_one::<String>("field"),
+    ) {
+        (Some(field_paths), _) => Some(field_paths.cloned().collect()),
+        (None, Some(field_path)) => Some(match matches.get_one::<String>("default") {
+            Some(default) => vec![format!("{}={}", field_path, default)],
+            None => vec![field_path.to_string()],
+        }),
+        (None, None) => None,
+    };
+    if let (Ok(export_format), Some(field_specs)) =
+        (output_format.parse::<ExportFormat>(), export_specs)
+    {
+        let options = ExportOptions {
+            prefix: matches.get_one::<String>("env-prefix").unwrap().to_string(),
+            separator: matches
+                .get_one::<String>("flatten-separator")
+                .unwrap()
+                .to_string(),
+            key_case: matches.get_one::<String>("key-case").unwrap().parse()?,
+        };
+
+        let vars = export_fields(file_path, &field_specs, &options)?;
+        match export_format {
+            ExportFormat::Dotenv => {
+                write_dotenv(matches.get_one::<String>("output-file").unwrap(), &vars)?
             }
-            Err(e) if !quiet => eprintln!("Error: {}", e),
-            _ => {}
+            ExportFormat::Gha => append_github_output(&vars)?,
+            _ => print!("{}", render_export(&vars, export_format)),
         }
         return Ok(());
     }
 
     // Handle multiple fields extraction
     if let Some(field_paths) = matches.get_many::<String>("multiple") {
+        if output_format == "toml" {
+            // Paths such as `package` and `package.name` overlap, so there is
+            // no single document to merge them into
+            return Err(TomlExtractError::InvalidArgument(
+                "Output format 'toml' takes a single field (-k), not -m".to_string(),
+            ));
+        }
         let field_paths: Vec<String> = field_paths.cloned().collect();
-        match extract_multiple_fields(file_path, &field_paths, strip_quotes) {
-            Ok(result) => {
-                if output_format == "json" {
-                    println!("{}", result.to_json()?);
-                } else if output_format == "json-pretty" {
-                    println!("{}", result.to_json_pretty()?);
-                } else {
-                    for (field_path, value) in result.fields {
-                        println!("{}: {}", field_path, value);
+        let result = extract_multiple_fields(file_path, &field_paths)?;
+        if output_format == "yaml" {
+            print!("{}", result.to_yaml()?);
+        } else if output_format == "json" {
+            println!("{}", result.to_json()?);
+        } else if output_format == "json-pretty" {
+            println!("{}", result.to_json_pretty()?);
+        } else if output_format == "nul" {
+            let paths: Vec<&str> = field_paths.iter().map(|s| split_default(s).0).collect();
+            print!("{}", result.to_nul(&paths));
+        } else {
+            for (field_path, value) in &result.fields {
+                match value {
+                    serde_json::Value::String(s) if strip_quotes || output_format == "lines" => {
+                        println!("{}: {}", field_path, s)
                     }
+                    _ => println!("{}: {}", field_path, value),
                 }
             }
-            Err(e) if !quiet => eprintln!("Error: {}", e),
-            _ => {}
+        }
+
+        // Partial results are printed above; missing fields still fail the command
+        if !result.is_complete() {
+            return Err(TomlExtractError::MissingFields(result.missing));
         }
     } else if let Some(field_path) = matches.get_one::<String>("field") {
         // Single field extraction
@@ -263,14 +362,20 @@ pub fn handle_get_command(matches: &clap::ArgMatches) -> Result<()> {
             field_path: field_path.to_string(),
             output_format: Some(output_format.to_string()),
             strip_quotes,
+            default: matches.get_one::<String>("default").cloned(),
         };
 
-        match extract_field(&config) {
-            Ok(result) => println!("{}", result),
-            Err(e) if !quiet => eprintln!("Error: {}", e),
-            _ => {}
-        }
+        print_output(&extract_field(&config)?, output_format);
     }
 
     Ok(())
 }
+
+/// Print a formatted value, NUL-terminated for the `nul` format
+fn print_output(result: &str, output_format: &str) {
+    if output_format == "nul" {
+        print!("{}\0", result);
+    } else {
+        println!("{}", result);
+    }
+}
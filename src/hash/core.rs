@@ -0,0 +1,120 @@
+//! Core functionality for hash and eq commands
+//!
+//! The canonical form of a value is a compact, single-line serialization in
+//! which table keys are sorted and always quoted, strings use one escaping
+//! style and floats are normalized (`-0.0` as `0.0`, every NaN as `nan`).
+//! Two values that TOML considers the same data therefore have the same
+//! canonical form, however they were written.
+
+use sha2::{Digest, Sha256};
+use toml::Value as TomlValue;
+
+use crate::document::load_toml;
+use crate::error::Result;
+use crate::flatten::inline_value;
+use crate::get::utils::get_nested_value;
+
+/// Serialize a value in canonical form
+pub fn canonical_string(value: &TomlValue) -> String {
+    let mut out = String::new();
+    write_canonical(value, &mut out);
+    out
+}
+
+fn write_canonical(value: &TomlValue, out: &mut String) {
+    match value {
+        TomlValue::String(_) => out.push_str(&inline_value(value)),
+        TomlValue::Float(f) if f.is_nan() => out.push_str("nan"),
+        TomlValue::Float(f) if *f == 0.0 => out.push_str("0.0"),
+        TomlValue::Array(array) => {
+            out.push('[');
+            for (i, elem) in array.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(elem, out);
+            }
+            out.push(']');
+        }
+        TomlValue::Table(table) => {
+            let mut entries: Vec<_> = table.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            out.push('{');
+            for (i, (key, child)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&inline_value(&TomlValue::String(key.clone())));
+                out.push('=');
+                write_canonical(child, out);
+            }
+            out.push('}');
+        }
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+/// SHA-256 of a value's canonical form, as lowercase hex
+pub fn canonical_hash(value: &TomlValue) -> String {
+    sha256_hex(canonical_string(value).as_bytes())
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Hash the value at `field_path` of a TOML file (the whole document if empty)
+pub fn hash_field(file_path: &str, field_path: &str) -> Result<String> {
+    let value = load_toml(file_path)?;
+    Ok(canonical_hash(get_nested_value(&value, field_path)?))
+}
+
+/// Whether two values are the same data, ignoring key order and formatting
+pub fn values_equal(a: &TomlValue, b: &TomlValue) -> bool {
+    canonical_string(a) == canonical_string(b)
+}
+
+/// Compare the values at `field_path` of two TOML files
+pub fn files_equal(a_path: &str, b_path: &str, field_path: &str) -> Result<bool> {
+    let a = load_toml(a_path)?;
+    let b = load_toml(b_path)?;
+    Ok(values_equal(
+        get_nested_value(&a, field_path)?,
+        get_nested_value(&b, field_path)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_string() {
+        let a: TomlValue = toml::from_str(
+            "[dependencies]\nserde = { version = \"1\", features = ['derive'] }\ntoml = \"0.8\"",
+        )
+        .unwrap();
+        let b: TomlValue = toml::from_str(
+            "[dependencies]\ntoml = '0.8'\n\n[dependencies.serde]\nfeatures = [\"derive\"]\nversion = \"1\"",
+        )
+        .unwrap();
+
+        assert_eq!(
+            canonical_string(&a["dependencies"]),
+            r#"{"serde"={"features"=["derive"],"version"="1"},"toml"="0.8"}"#
+        );
+        assert!(values_equal(&a, &b));
+        assert_eq!(canonical_hash(&a), canonical_hash(&b));
+    }
+
+    #[test]
+    fn test_canonical_distinguishes_types() {
+        let value: TomlValue = toml::from_str("i = 1\nf = 1.0\ns = \"1\"\nz = -0.0").unwrap();
+        assert!(!values_equal(&value["i"], &value["f"]));
+        assert!(!values_equal(&value["i"], &value["s"]));
+        assert_eq!(canonical_string(&value["z"]), "0.0");
+    }
+}
@@ -0,0 +1,68 @@
+//! CLI definitions and handling for hash and eq commands
+
+use clap::{Arg, ArgMatches, Command};
+
+use super::core::*;
+use crate::error::Result;
+
+fn field_arg() -> Arg {
+    Arg::new("field")
+        .short('k')
+        .long("field")
+        .value_name("FIELD")
+        .help("Field path of the subtree (the whole document if omitted)")
+        .default_value("")
+}
+
+/// Define the hash command CLI structure
+pub fn hash_command() -> Command {
+    Command::new("hash")
+        .about("Print a SHA-256 of a subtree that ignores key order, whitespace and quoting style")
+        .arg(
+            Arg::new("file")
+                .short('f')
+                .long("file")
+                .value_name("FILE")
+                .help("TOML file path")
+                .default_value("Cargo.toml"),
+        )
+        .arg(field_arg())
+}
+
+/// Define the eq command CLI structure
+pub fn eq_command() -> Command {
+    Command::new("eq")
+        .about("Check whether two TOML files hold the same data (answers through the exit status only)")
+        .arg(
+            Arg::new("a")
+                .value_name("A")
+                .help("First TOML file")
+                .required(true),
+        )
+        .arg(
+            Arg::new("b")
+                .value_name("B")
+                .help("Second TOML file")
+                .required(true),
+        )
+        .arg(field_arg())
+}
+
+/// Handle hash command logic
+pub fn handle_hash_command(matches: &ArgMatches) -> Result<()> {
+    let file_path = matches.get_one::<String>("file").unwrap();
+    let field_path = matches.get_one::<String>("field").unwrap();
+    println!("{}", hash_field(file_path, field_path)?);
+    Ok(())
+}
+
+/// Handle eq command logic
+///
+/// Prints nothing; the caller turns `Ok(false)` into exit status 5.
+pub fn handle_eq_command(matches: &ArgMatches) -> Result<bool> {
+    files_equal(
+        matches.get_one::<String>("a").unwrap(),
+        matches.get_one::<String>("b").unwrap(),
+        matches.get_one::<String>("field").unwrap(),
+    )
+}
@@ -12,8 +12,48 @@
 pub mod error;
 pub use error::TomlExtractError;
 
+pub mod document;
+pub mod path;
+
+#[cfg(feature = "assert")]
+#[cfg_attr(docsrs, doc(cfg(feature = "assert")))]
+pub mod assert;
+#[cfg(feature = "codegen")]
+#[cfg_attr(docsrs, doc(cfg(feature = "codegen")))]
+pub mod codegen;
+#[cfg(feature = "diff")]
+#[cfg_attr(docsrs, doc(cfg(feature = "diff")))]
+pub mod diff;
+#[cfg(feature = "flatten")]
+#[cfg_attr(docsrs, doc(cfg(feature = "flatten")))]
+pub mod flatten;
+#[cfg(feature = "fmt")]
+#[cfg_attr(docsrs, doc(cfg(feature = "fmt")))]
+pub mod fmt;
 pub mod get;
+#[cfg(feature = "hash")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hash")))]
+pub mod hash;
+#[cfg(feature = "query")]
+#[cfg_attr(docsrs, doc(cfg(feature = "query")))]
+pub mod query;
+#[cfg(feature = "schema")]
+#[cfg_attr(docsrs, doc(cfg(feature = "schema")))]
+pub mod schema;
 pub mod set;
+#[cfg(feature = "sort")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sort")))]
+pub mod sort;
+
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub mod convert;
+#[cfg(feature = "patch")]
+#[cfg_attr(docsrs, doc(cfg(feature = "patch")))]
+pub mod patch;
+#[cfg(feature = "validate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "validate")))]
+pub mod validate;
 
 // Re-export core types for convenience
 pub use get::types::ExtractConfig;
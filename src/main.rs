@@ -1,36 +1,143 @@
 //! CLI entry point for i_edit_toml - a TOML field extraction and manipulation tool
 
-use anyhow::{Context, Result};
 use clap::Command;
+use i_edit_toml::assert::xcli::{assert_command, handle_assert_command};
+use i_edit_toml::codegen::xcli::{codegen_command, handle_codegen_command};
+use i_edit_toml::convert::xcli::{convert_command, handle_convert_command};
+use i_edit_toml::diff::xcli::{diff_command, handle_diff_command};
+use i_edit_toml::flatten::xcli::{
+    handle_paths_command, handle_unflatten_command, paths_command, unflatten_command,
+};
+use i_edit_toml::fmt::xcli::{fmt_command, handle_fmt_command};
+use i_edit_toml::hash::xcli::{eq_command, handle_eq_command, handle_hash_command, hash_command};
+use i_edit_toml::patch::xcli::{
+    handle_merge_patch_command, handle_patch_command, merge_patch_command, patch_command,
+};
+use i_edit_toml::query::xcli::{
+    exists_command, handle_exists_command, handle_query_command, keys_command, len_command,
+    tree_command, type_command,
+};
+use i_edit_toml::schema::xcli::{handle_schema_command, schema_command};
+use i_edit_toml::sort::xcli::{handle_sort_command, sort_command};
+use i_edit_toml::validate::xcli::{handle_validate_command, validate_command};
 use i_edit_toml::{get::xcli::get_command, set::xcli::cli as set_command};
 
-fn main() -> Result<()> {
+const EXIT_CODES_HELP: &str = "\
+Exit codes:
+  0  success / field found / files equal
+  1  field not found / unformatted (fmt --check) / invalid (validate) / rule failed (assert) / no match (sort)
+  2  parse error (TOML, JSON, YAML) / malformed rule (assert)
+  3  I/O error
+  4  type mismatch
+  5  files differ (diff, eq)
+  64 invalid command line (unknown option or value, unsupported combination, bad schema or patch, ...)";
+
+/// Exit status of `diff` and `eq` when the documents differ
+const EXIT_DIFFERENT: i32 = 5;
+
+/// Exit status for command line usage errors (`EX_USAGE` from sysexits.h),
+/// kept apart from the parse error status 2 that clap would use
+const EXIT_USAGE: i32 = 64;
+
+fn main() {
     // Define main CLI structure
     let mut app = Command::new("i_edit_toml")
         .version(env!("CARGO_PKG_VERSION"))
         .author("YeMiancheng <ymc.github@gmail.com>")
         .about("A lightweight, high-performance TOML field extraction and manipulation tool")
+        .after_help(EXIT_CODES_HELP)
         .subcommand(get_command().name("get"))
-        .subcommand(set_command().name("set"));
+        .subcommand(set_command().name("set"))
+        .subcommand(exists_command())
+        .subcommand(type_command())
+        .subcommand(keys_command())
+        .subcommand(len_command())
+        .subcommand(tree_command())
+        .subcommand(paths_command())
+        .subcommand(unflatten_command())
+        .subcommand(convert_command())
+        .subcommand(diff_command())
+        .subcommand(patch_command())
+        .subcommand(merge_patch_command())
+        .subcommand(hash_command())
+        .subcommand(eq_command())
+        .subcommand(fmt_command())
+        .subcommand(sort_command())
+        .subcommand(validate_command())
+        .subcommand(schema_command())
+        .subcommand(codegen_command())
+        .subcommand(assert_command());
 
     // Parse CLI arguments
-    let matches = app.clone().get_matches();
+    let matches = app.clone().try_get_matches().unwrap_or_else(|e| {
+        // `--help` and `--version` also arrive here, printed to stdout
+        let _ = e.print();
+        std::process::exit(if e.use_stderr() { EXIT_USAGE } else { 0 });
+    });
 
     // Dispatch to appropriate subcommand handler
-    match matches.subcommand() {
-        Some(("get", sub_matches)) => {
-            i_edit_toml::get::xcli::handle_get_command(sub_matches)
-                .context("Failed to execute get command")?;
-        }
-        Some(("set", sub_matches)) => {
-            i_edit_toml::set::xcli::handle_set_command(sub_matches)
-                .context("Failed to execute set command")?;
+    let result = match matches.subcommand() {
+        Some(("get", sub_matches)) => i_edit_toml::get::xcli::handle_get_command(sub_matches),
+        Some(("set", sub_matches)) => i_edit_toml::set::xcli::handle_set_command(sub_matches),
+        Some(("exists", sub_matches)) => handle_exists_command(sub_matches).map(|found| {
+            if !found {
+                std::process::exit(1);
+            }
+        }),
+        Some((name @ ("type" | "keys" | "len" | "tree"), sub_matches)) => {
+            handle_query_command(name, sub_matches)
         }
+        Some(("paths", sub_matches)) => handle_paths_command(sub_matches),
+        Some(("unflatten", sub_matches)) => handle_unflatten_command(sub_matches),
+        Some(("convert", sub_matches)) => handle_convert_command(sub_matches),
+        Some(("diff", sub_matches)) => handle_diff_command(sub_matches).map(|differ| {
+            if differ {
+                std::process::exit(EXIT_DIFFERENT);
+            }
+        }),
+        Some(("patch", sub_matches)) => handle_patch_command(sub_matches),
+        Some(("merge-patch", sub_matches)) => handle_merge_patch_command(sub_matches),
+        Some(("hash", sub_matches)) => handle_hash_command(sub_matches),
+        Some(("eq", sub_matches)) => handle_eq_command(sub_matches).map(|equal| {
+            if !equal {
+                std::process::exit(EXIT_DIFFERENT);
+            }
+        }),
+        Some(("fmt", sub_matches)) => handle_fmt_command(sub_matches).map(|formatted| {
+            if !formatted {
+                std::process::exit(1);
+            }
+        }),
+        Some(("sort", sub_matches)) => handle_sort_command(sub_matches),
+        Some(("validate", sub_matches)) => handle_validate_command(sub_matches).map(|valid| {
+            if !valid {
+                std::process::exit(1);
+            }
+        }),
+        Some(("schema", sub_matches)) => handle_schema_command(sub_matches),
+        Some(("codegen", sub_matches)) => handle_codegen_command(sub_matches),
+        Some(("assert", sub_matches)) => handle_assert_command(sub_matches).map(|held| {
+            if !held {
+                std::process::exit(1);
+            }
+        }),
         _ => {
             // Print help if no subcommand is provided
             println!("{}", app.render_help());
+            Ok(())
         }
-    }
+    };
 
-    Ok(())
+    if let Err(e) = result {
+        // `--quiet` only silences the message; the exit status still reports the failure
+        let quiet = matches
+            .subcommand()
+            .and_then(|(_, sub_matches)| sub_matches.try_get_one::<bool>("quiet").ok().flatten())
+            .copied()
+            .unwrap_or(false);
+        if !quiet {
+            eprintln!("{}", e.diagnostic());
+        }
+        std::process::exit(e.exit_code());
+    }
 }
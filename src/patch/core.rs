@@ -0,0 +1,299 @@
+//! Core functionality for patch command
+//!
+//! Applies JSON Patch documents ([RFC 6902]) to TOML values. JSON Pointer
+//! paths are translated to [`PathSegment`]s against the document being
+//! patched, so a numeric token indexes an array and names a key in a table.
+//! Operations run on a copy of the document, which only replaces the
+//! original once every operation has succeeded.
+//!
+//! [RFC 6902]: https://www.rfc-editor.org/rfc/rfc6902
+
+use serde_json::Value as JsonValue;
+use toml::Value as TomlValue;
+
+use crate::convert::{json_to_toml_value, NullHandling};
+use crate::document::{load_toml, parse_json, read_input};
+use crate::error::{Result, TomlExtractError};
+use crate::flatten::inline_value;
+use crate::get::utils::{get_value_at, type_name};
+use crate::path::{format_path, remove_value_at, set_value_at, value_at_mut, PathSegment};
+use crate::set::save_toml;
+
+/// Apply a patch file (`-` for stdin) to a TOML file and write the result back
+///
+/// The TOML file is left untouched if any operation fails.
+pub fn patch_file(file_path: &str, patch_path: &str) -> Result<()> {
+    let patch: JsonValue = parse_json(&read_input(patch_path)?, patch_path)?;
+    save_toml(file_path, &patched_document(file_path, &patch)?)
+}
+
+/// Apply a patch to a TOML file and return the updated content
+pub fn patch_toml(file_path: &str, patch: &JsonValue) -> Result<String> {
+    Ok(toml::to_string_pretty(&patched_document(
+        file_path, patch,
+    )?)?)
+}
+
+fn patched_document(file_path: &str, patch: &JsonValue) -> Result<TomlValue> {
+    let mut document = load_toml(file_path)?;
+    apply_patch(&mut document, patch)?;
+    Ok(document)
+}
+
+/// Apply a JSON Patch, an array of operations, to a document
+///
+/// # Errors
+/// Returns [`TomlExtractError::PatchFailed`] naming the first failing
+/// operation; `document` is then unchanged.
+pub fn apply_patch(document: &mut TomlValue, patch: &JsonValue) -> Result<()> {
+    let operations = patch.as_array().ok_or_else(|| {
+        TomlExtractError::InvalidArgument("A JSON Patch must be an array of operations".into())
+    })?;
+
+    let mut patched = document.clone();
+    for (index, operation) in operations.iter().enumerate() {
+        let op = operation
+            .get("op")
+            .and_then(JsonValue::as_str)
+            .unwrap_or_default();
+        apply_operation(&mut patched, op, operation).map_err(|e| {
+            TomlExtractError::PatchFailed {
+                index,
+                op: op.to_string(),
+                source: Box::new(e),
+            }
+        })?;
+    }
+
+    *document = patched;
+    Ok(())
+}
+
+fn apply_operation(document: &mut TomlValue, op: &str, operation: &JsonValue) -> Result<()> {
+    let path = pointer_member(operation, "path")?;
+
+    match op {
+        "add" => {
+            let value = value_member(operation, &path)?;
+            add(document, &pointer_segments(document, &path)?, value)
+        }
+        "remove" => remove_value_at(document, &pointer_segments(document, &path)?).map(drop),
+        "replace" => {
+            let value = value_member(operation, &path)?;
+            let segments = pointer_segments(document, &path)?;
+            get_value_at(document, &segments)?;
+            if segments.is_empty() {
+                return replace_root(document, value);
+            }
+            set_value_at(document, &segments, value, false)
+        }
+        "move" => {
+            let from = pointer_member(operation, "from")?;
+            if path.starts_with(&format!("{}/", from)) {
+                return Err(TomlExtractError::InvalidFieldPath(format!(
+                    "Cannot move {} into its own child {}",
+                    from, path
+                )));
+            }
+            let value = remove_value_at(document, &pointer_segments(document, &from)?)?;
+            add(document, &pointer_segments(document, &path)?, value)
+        }
+        "copy" => {
+            let from = pointer_member(operation, "from")?;
+            let value = get_value_at(document, &pointer_segments(document, &from)?)?.clone();
+            add(document, &pointer_segments(document, &path)?, value)
+        }
+        "test" => {
+            let expected = value_member(operation, &path)?;
+            let segments = pointer_segments(document, &path)?;
+            let found = get_value_at(document, &segments)?;
+            if *found != expected {
+                return Err(TomlExtractError::TestFailed {
+                    path: format_path(&segments),
+                    expected: inline_value(&expected),
+                    found: inline_value(found),
+                });
+            }
+            Ok(())
+        }
+        other => Err(TomlExtractError::InvalidArgument(format!(
+            "Unknown patch operation: '{}'",
+            other
+        ))),
+    }
+}
+
+/// Add `value` at `segments`: insert into an array, or set a table key
+fn add(document: &mut TomlValue, segments: &[PathSegment], value: TomlValue) -> Result<()> {
+    let Some((last, parent_segments)) = segments.split_last() else {
+        return replace_root(document, value);
+    };
+    let parent_type = type_name(get_value_at(document, parent_segments)?);
+
+    match (last, value_at_mut(document, parent_segments)) {
+        (PathSegment::Index(index), Some(TomlValue::Array(array))) => {
+            if *index > array.len() {
+                return Err(TomlExtractError::ArrayIndexOutOfBounds {
+                    path: format_path(parent_segments),
+                    index: *index,
+                    length: array.len(),
+                });
+            }
+            array.insert(*index, value);
+            Ok(())
+        }
+        (PathSegment::Key(_), Some(TomlValue::Table(_))) => {
+            set_value_at(document, segments, value, false)
+        }
+        _ => Err(TomlExtractError::TypeMismatch {
+            path: format_path(parent_segments),
+            expected: "table or array".to_string(),
+            found: parent_type.to_string(),
+        }),
+    }
+}
+
+fn replace_root(document: &mut TomlValue, value: TomlValue) -> Result<()> {
+    if !value.is_table() {
+        return Err(TomlExtractError::TypeMismatch {
+            path: String::new(),
+            expected: "table".to_string(),
+            found: type_name(&value).to_string(),
+        });
+    }
+    *document = value;
+    Ok(())
+}
+
+/// Translate a JSON Pointer into path segments against `document`
+///
+/// A token indexes an array when the value it applies to is an array (`-`
+/// meaning one past the end), and is a table key otherwise.
+pub fn pointer_segments(document: &TomlValue, pointer: &str) -> Result<Vec<PathSegment>> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(tokens) = pointer.strip_prefix('/') else {
+        return Err(TomlExtractError::InvalidFieldPath(format!(
+            "JSON Pointer must be empty or start with '/': {}",
+            pointer
+        )));
+    };
+
+    let mut segments = Vec::new();
+    let mut current = Some(document);
+    for token in tokens.split('/') {
+        let token = token.replace("~1", "/").replace("~0", "~");
+        let segment = match current {
+            Some(TomlValue::Array(array)) if token == "-" => PathSegment::Index(array.len()),
+            Some(TomlValue::Array(_)) => {
+                let valid = !token.is_empty()
+                    && token.bytes().all(|b| b.is_ascii_digit())
+                    && (token == "0" || !token.starts_with('0'));
+                let index = token.parse().ok().filter(|_| valid).ok_or_else(|| {
+                    TomlExtractError::InvalidArrayIndex(format!(
+                        "'{}' in JSON Pointer {}",
+                        token, pointer
+                    ))
+                })?;
+                PathSegment::Index(index)
+            }
+            _ => PathSegment::Key(token),
+        };
+        current = current.and_then(|value| match &segment {
+            PathSegment::Key(key) => value.get(key.as_str()),
+            PathSegment::Index(index) => value.get(*index),
+        });
+        segments.push(segment);
+    }
+
+    Ok(segments)
+}
+
+fn pointer_member(operation: &JsonValue, name: &str) -> Result<String> {
+    operation
+        .get(name)
+        .and_then(JsonValue::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| {
+            TomlExtractError::InvalidArgument(format!("Operation is missing a '{}' string", name))
+        })
+}
+
+fn value_member(operation: &JsonValue, path: &str) -> Result<TomlValue> {
+    let value = operation.get("value").ok_or_else(|| {
+        TomlExtractError::InvalidArgument("Operation is missing a 'value'".to_string())
+    })?;
+    Ok(json_to_toml_value(value, path, NullHandling::Error)?.expect("null is rejected"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample() -> TomlValue {
+        toml::from_str(
+            "[package]\nname = \"demo\"\nkeywords = [\"a\", \"c\"]\n\n[dependencies]\ntoml = \"0.8\"",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_apply_patch() {
+        let mut document = sample();
+        apply_patch(
+            &mut document,
+            &json!([
+                {"op": "test", "path": "/package/name", "value": "demo"},
+                {"op": "add", "path": "/package/keywords/1", "value": "b"},
+                {"op": "add", "path": "/package/keywords/-", "value": "d"},
+                {"op": "replace", "path": "/package/name", "value": "renamed"},
+                {"op": "copy", "from": "/dependencies/toml", "path": "/dependencies/toml_edit"},
+                {"op": "move", "from": "/dependencies/toml", "path": "/dependencies/serde"},
+                {"op": "remove", "path": "/package/keywords/0"},
+                {"op": "add", "path": "/package/a~1b", "value": {"x": 1}}
+            ]),
+        )
+        .unwrap();
+
+        let expected: TomlValue = toml::from_str(
+            "[package]\nname = \"renamed\"\nkeywords = [\"b\", \"c\", \"d\"]\n\"a/b\" = { x = 1 }\n\n[dependencies]\ntoml_edit = \"0.8\"\nserde = \"0.8\"",
+        )
+        .unwrap();
+        assert_eq!(document, expected);
+    }
+
+    #[test]
+    fn test_apply_patch_is_atomic() {
+        let mut document = sample();
+        let err = apply_patch(
+            &mut document,
+            &json!([
+                {"op": "replace", "path": "/package/name", "value": "renamed"},
+                {"op": "test", "path": "/package/name", "value": "demo"}
+            ]),
+        )
+        .unwrap_err();
+
+        assert_eq!(err.exit_code(), 1);
+        assert!(err
+            .to_string()
+            .starts_with("Patch operation 1 (test) failed"));
+        assert_eq!(document, sample());
+
+        let err = apply_patch(&mut document, &json!([{"op": "rename", "path": "/a"}]));
+        assert_eq!(err.unwrap_err().exit_code(), 64);
+    }
+
+    #[test]
+    fn test_pointer_segments() {
+        let document = sample();
+        assert_eq!(
+            format_path(&pointer_segments(&document, "/package/keywords/1").unwrap()),
+            "package.keywords[1]"
+        );
+        assert!(pointer_segments(&document, "/package/keywords/01").is_err());
+        assert!(pointer_segments(&document, "package").is_err());
+    }
+}
@@ -0,0 +1,194 @@
+//! JSON Merge Patch ([RFC 7396]) for TOML files
+//!
+//! A merge patch is a partial object: its tables are merged key by key into
+//! the target, any other value replaces the target's value, and `null`
+//! removes a key. Patches written in TOML cannot contain `null`, so they
+//! only ever add or replace.
+//!
+//! [RFC 7396]: https://www.rfc-editor.org/rfc/rfc7396
+
+use serde_json::Value as JsonValue;
+use toml::{Table, Value as TomlValue};
+
+use crate::convert::{
+    json_to_toml_value, tagged_datetime, toml_to_json_value, DatetimeStyle, DocumentFormat,
+    NullHandling,
+};
+#[cfg(feature = "yaml")]
+use crate::document::parse_yaml;
+use crate::document::{load_toml, parse_json, parse_toml, read_input};
+use crate::error::{Result, TomlExtractError};
+use crate::path::{format_path, parse_path, set_value_at, value_at_mut, PathSegment};
+use crate::set::save_toml;
+
+/// Read a merge patch (`-` for stdin) written in `format`
+///
+/// TOML patches go through the same conversion as `convert`, so their
+/// datetimes survive the merge.
+pub fn read_merge_patch(patch_path: &str, format: DocumentFormat) -> Result<JsonValue> {
+    let content = read_input(patch_path)?;
+    match format {
+        DocumentFormat::Json => parse_json(&content, patch_path),
+        DocumentFormat::Toml => {
+            toml_to_json_value(&parse_toml(&content, patch_path)?, DatetimeStyle::Tagged)
+        }
+        #[cfg(feature = "yaml")]
+        DocumentFormat::Yaml => parse_yaml(&content, patch_path),
+        #[cfg(not(feature = "yaml"))]
+        DocumentFormat::Yaml => Err(TomlExtractError::InvalidArgument(
+            "YAML patches require the `yaml` feature".to_string(),
+        )),
+    }
+}
+
+/// Merge a patch into the value at `field_path` of a TOML file and return the updated content
+///
+/// An empty `field_path` merges into the whole document; a missing one is
+/// created as a table first.
+pub fn merge_patch_toml(file_path: &str, field_path: &str, patch: &JsonValue) -> Result<String> {
+    let document = merged_document(file_path, field_path, patch)?;
+    Ok(toml::to_string_pretty(&document)?)
+}
+
+/// Merge a patch into a TOML file and write the result back
+pub fn merge_patch_file(file_path: &str, field_path: &str, patch: &JsonValue) -> Result<()> {
+    save_toml(file_path, &merged_document(file_path, field_path, patch)?)
+}
+
+fn merged_document(file_path: &str, field_path: &str, patch: &JsonValue) -> Result<TomlValue> {
+    let mut document = load_toml(file_path)?;
+    let segments = parse_path(field_path)?;
+
+    if segments.is_empty() {
+        merge_patch(&mut document, patch)?;
+    } else {
+        if value_at_mut(&mut document, &segments).is_none() {
+            set_value_at(
+                &mut document,
+                &segments,
+                TomlValue::Table(Table::new()),
+                false,
+            )?;
+        }
+        let target = value_at_mut(&mut document, &segments).expect("created above");
+        merge_into(target, patch, &mut segments.clone())?;
+    }
+
+    Ok(document)
+}
+
+/// Merge a patch into a whole document
+///
+/// # Errors
+/// The patch must be an object, since a TOML document is always a table.
+pub fn merge_patch(document: &mut TomlValue, patch: &JsonValue) -> Result<()> {
+    if !patch.is_object() {
+        return Err(TomlExtractError::TypeMismatch {
+            path: String::new(),
+            expected: "object".to_string(),
+            found: json_type_name(patch).to_string(),
+        });
+    }
+    merge_into(document, patch, &mut Vec::new())
+}
+
+fn merge_into(
+    target: &mut TomlValue,
+    patch: &JsonValue,
+    segments: &mut Vec<PathSegment>,
+) -> Result<()> {
+    let object = match patch {
+        JsonValue::Object(object) if tagged_datetime(object).is_none() => object,
+        _ => {
+            let path = format_path(segments);
+            *target =
+                json_to_toml_value(patch, &path, NullHandling::Error)?.expect("null is rejected");
+            return Ok(());
+        }
+    };
+
+    if !target.is_table() {
+        *target = TomlValue::Table(Table::new());
+    }
+    let table = target.as_table_mut().expect("checked above");
+    for (key, child) in object {
+        if child.is_null() {
+            table.remove(key);
+            continue;
+        }
+        segments.push(PathSegment::Key(key.clone()));
+        let entry = table
+            .entry(key.clone())
+            .or_insert_with(|| TomlValue::Table(Table::new()));
+        merge_into(entry, child, segments)?;
+        segments.pop();
+    }
+    Ok(())
+}
+
+fn json_type_name(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::Bool(_) => "boolean",
+        JsonValue::Number(_) => "number",
+        JsonValue::String(_) => "string",
+        JsonValue::Array(_) => "array",
+        JsonValue::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get::utils::type_name;
+    use serde_json::json;
+
+    #[test]
+    fn test_merge_patch() {
+        let mut document: TomlValue = toml::from_str(
+            "[package]\nname = \"demo\"\nreadme = \"README.md\"\nkeywords = [\"a\"]\n\n[features]\ndefault = []",
+        )
+        .unwrap();
+        merge_patch(
+            &mut document,
+            &json!({
+                "package": {"readme": null, "keywords": ["b", "c"], "license": "MIT"},
+                "features": "none",
+                "released": {"$datetime": "2024-05-01"},
+                "absent": null
+            }),
+        )
+        .unwrap();
+
+        let expected: TomlValue = toml::from_str(
+            "features = \"none\"\nreleased = 2024-05-01\n\n[package]\nname = \"demo\"\nkeywords = [\"b\", \"c\"]\nlicense = \"MIT\"",
+        )
+        .unwrap();
+        assert_eq!(document, expected);
+        assert_eq!(type_name(&document["released"]), "datetime");
+    }
+
+    #[test]
+    fn test_merge_patch_rejects_non_object() {
+        let mut document = TomlValue::Table(Table::new());
+        assert!(matches!(
+            merge_patch(&mut document, &json!([1])),
+            Err(TomlExtractError::TypeMismatch { .. })
+        ));
+        assert!(merge_patch(&mut document, &json!({"a": [null]})).is_err());
+    }
+
+    #[test]
+    fn test_merge_patch_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("Cargo.toml");
+        std::fs::write(&file, "[package]\nname = \"demo\"\n").unwrap();
+        let file = file.to_str().unwrap();
+
+        let patch = json!({"version": "0.2.0"});
+        let expected = merge_patch_toml(file, "package", &patch).unwrap();
+        merge_patch_file(file, "package", &patch).unwrap();
+        assert_eq!(std::fs::read_to_string(file).unwrap(), expected);
+        assert!(expected.contains("version = \"0.2.0\""));
+    }
+}
@@ -0,0 +1,12 @@
+//! JSON Patch (RFC 6902) and JSON Merge Patch (RFC 7396) support for TOML files
+
+pub mod core;
+pub mod merge;
+#[cfg(feature = "cli")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cli")))]
+pub mod xcli;
+
+pub use core::*;
+pub use merge::*;
+#[cfg(feature = "cli")]
+pub use xcli::*;
@@ -0,0 +1,139 @@
+//! CLI definitions and handling for patch command
+
+use clap::{Arg, ArgMatches, Command};
+use serde_json::Value as JsonValue;
+
+use super::core::*;
+use super::merge::*;
+use crate::convert::DocumentFormat;
+use crate::document::{parse_json, read_input};
+use crate::error::Result;
+
+/// Define the patch command CLI structure
+pub fn patch_command() -> Command {
+    Command::new("patch")
+        .about("Apply a JSON Patch (RFC 6902) to a TOML file; nothing is written if any operation fails")
+        .arg(
+            Arg::new("file")
+                .short('f')
+                .long("file")
+                .value_name("FILE")
+                .help("TOML file path")
+                .default_value("Cargo.toml"),
+        )
+        .arg(
+            Arg::new("patch")
+                .short('p')
+                .long("patch")
+                .value_name("PATCH")
+                .help("JSON Patch file (- for stdin)")
+                .required(true),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .help("Print the patched document instead of writing it")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("quiet")
+                .short('q')
+                .long("quiet")
+                .help("Suppress status and error messages (the exit status still reports failures)")
+                .action(clap::ArgAction::SetTrue),
+        )
+}
+
+/// Define the merge-patch command CLI structure
+pub fn merge_patch_command() -> Command {
+    Command::new("merge-patch")
+        .about("Deep-merge a JSON Merge Patch (RFC 7396) into a TOML file; null deletes a key")
+        .arg(
+            Arg::new("file")
+                .short('f')
+                .long("file")
+                .value_name("FILE")
+                .help("TOML file path")
+                .default_value("Cargo.toml"),
+        )
+        .arg(
+            Arg::new("patch")
+                .short('p')
+                .long("patch")
+                .value_name("PATCH")
+                .help("Partial object to merge, as JSON, YAML or TOML (- for stdin)")
+                .required(true),
+        )
+        .arg(
+            Arg::new("from")
+                .long("from")
+                .value_name("FORMAT")
+                .help("Patch format (json, yaml, toml); guessed from the extension, JSON otherwise")
+                .value_parser(["json", "yaml", "toml"]),
+        )
+        .arg(
+            Arg::new("field")
+                .short('k')
+                .long("field")
+                .value_name("FIELD")
+                .help("Merge into the table at this field path instead of the document root")
+                .default_value(""),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .help("Print the merged document instead of writing it")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("quiet")
+                .short('q')
+                .long("quiet")
+                .help("Suppress status and error messages (the exit status still reports failures)")
+                .action(clap::ArgAction::SetTrue),
+        )
+}
+
+/// Handle patch command logic
+pub fn handle_patch_command(matches: &ArgMatches) -> Result<()> {
+    let file_path = matches.get_one::<String>("file").unwrap();
+    let patch_path = matches.get_one::<String>("patch").unwrap();
+
+    if matches.get_flag("dry-run") {
+        let patch: JsonValue = parse_json(&read_input(patch_path)?, patch_path)?;
+        print!("{}", patch_toml(file_path, &patch)?);
+        return Ok(());
+    }
+
+    patch_file(file_path, patch_path)?;
+    if !matches.get_flag("quiet") {
+        println!("✅ Patched {}", file_path);
+    }
+    Ok(())
+}
+
+/// Handle merge-patch command logic
+pub fn handle_merge_patch_command(matches: &ArgMatches) -> Result<()> {
+    let file_path = matches.get_one::<String>("file").unwrap();
+    let field_path = matches.get_one::<String>("field").unwrap();
+    let patch_path = matches.get_one::<String>("patch").unwrap();
+    let format = match matches.get_one::<String>("from") {
+        Some(from) => from.parse()?,
+        None => match DocumentFormat::from_path(patch_path) {
+            DocumentFormat::Toml if !patch_path.ends_with(".toml") => DocumentFormat::Json,
+            format => format,
+        },
+    };
+    let patch = read_merge_patch(patch_path, format)?;
+
+    if matches.get_flag("dry-run") {
+        print!("{}", merge_patch_toml(file_path, field_path, &patch)?);
+        return Ok(());
+    }
+
+    merge_patch_file(file_path, field_path, &patch)?;
+    if !matches.get_flag("quiet") {
+        println!("✅ Merged patch into {}", file_path);
+    }
+    Ok(())
+}
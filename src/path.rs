@@ -0,0 +1,355 @@
+//! Field path syntax shared by all commands
+//!
+//! A path is a sequence of keys separated by `.`, each optionally followed by
+//! array indices: `package.name`, `bin[1].name`, `matrix[0][2]`. Keys that
+//! are not bare (`A-Za-z0-9_-`) are quoted as in TOML, either `"double"`
+//! with `\"` and `\\` escapes or `'literal'`: `target."cfg(unix)".dependencies`.
+
+use std::fmt;
+use toml::{Table, Value as TomlValue};
+
+use crate::error::{Result, TomlExtractError};
+
+/// One step of a field path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    /// Table key
+    Key(String),
+    /// Array index
+    Index(usize),
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Key(key) => f.write_str(&format_key(key)),
+            Self::Index(index) => write!(f, "[{}]", index),
+        }
+    }
+}
+
+/// Parse a field path into segments; an empty path has no segments
+///
+/// # Errors
+/// Returns [`TomlExtractError::InvalidFieldPath`] for empty keys or
+/// unterminated quotes and [`TomlExtractError::InvalidArrayIndex`] for
+/// malformed indices.
+pub fn parse_path(path: &str) -> Result<Vec<PathSegment>> {
+    let mut segments = Vec::new();
+    let mut chars = path.chars().peekable();
+    let invalid = |message: &str| {
+        TomlExtractError::InvalidFieldPath(format!("{} in path '{}'", message, path))
+    };
+
+    while chars.peek().is_some() {
+        // Key
+        let key = match chars.peek() {
+            Some(&q @ ('"' | '\'')) => {
+                chars.next();
+                let mut key = String::new();
+                loop {
+                    match chars.next() {
+                        Some(c) if c == q => break,
+                        Some('\\') if q == '"' => match chars.next() {
+                            Some('n') => key.push('\n'),
+                            Some('t') => key.push('\t'),
+                            Some(c) => key.push(c),
+                            None => return Err(invalid("Unterminated quoted key")),
+                        },
+                        Some(c) => key.push(c),
+                        None => return Err(invalid("Unterminated quoted key")),
+                    }
+                }
+                key
+            }
+            _ => {
+                let mut key = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c == '.' || c == '[' {
+                        break;
+                    }
+                    key.push(c);
+                    chars.next();
+                }
+                if key.is_empty() {
+                    return Err(invalid("Empty path segment"));
+                }
+                key
+            }
+        };
+        segments.push(PathSegment::Key(key));
+
+        // Indices
+        while chars.peek() == Some(&'[') {
+            chars.next();
+            let mut digits = String::new();
+            loop {
+                match chars.next() {
+                    Some(']') => break,
+                    Some(c) => digits.push(c),
+                    None => return Err(invalid("Unclosed '['")),
+                }
+            }
+            let index = digits.trim().parse::<usize>().map_err(|_| {
+                TomlExtractError::InvalidArrayIndex(format!("Invalid array index: {}", digits))
+            })?;
+            segments.push(PathSegment::Index(index));
+        }
+
+        match chars.next() {
+            None => break,
+            Some('.') if chars.peek().is_some() => {}
+            Some('.') => return Err(invalid("Trailing '.'")),
+            Some(c) => return Err(invalid(&format!("Unexpected '{}'", c))),
+        }
+    }
+
+    Ok(segments)
+}
+
+/// Format segments back into a path that [`parse_path`] accepts
+pub fn format_path(segments: &[PathSegment]) -> String {
+    let mut path = String::new();
+    for segment in segments {
+        if matches!(segment, PathSegment::Key(_)) && !path.is_empty() {
+            path.push('.');
+        }
+        path.push_str(&segment.to_string());
+    }
+    path
+}
+
+/// Format a single key, quoting it unless it is a bare key
+pub fn format_key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        key.to_string()
+    } else {
+        format!(
+            "\"{}\"",
+            key.replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n")
+                .replace('\t', "\\t")
+        )
+    }
+}
+
+/// Split `text` at the first `delimiter` outside a quoted key
+///
+/// Quotes follow the rules of [`parse_path`], so `"a=b"=x` splits at the
+/// second `=`.
+pub fn split_unquoted(text: &str, delimiter: char) -> Option<(&str, &str)> {
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (Some('"'), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            }
+            (Some(q), c) if c == q && !escaped => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, c) if c == delimiter => return Some((&text[..i], &text[i + c.len_utf8()..])),
+            _ => {}
+        }
+        escaped = false;
+    }
+    None
+}
+
+/// Set `new_value` at `segments`, creating intermediate tables as needed
+///
+/// With `create_missing`, scalars in the way are replaced by tables or
+/// arrays, and arrays are padded with empty strings up to the index.
+/// Otherwise an index must already exist.
+pub fn set_value_at(
+    current: &mut TomlValue,
+    segments: &[PathSegment],
+    new_value: TomlValue,
+    create_missing: bool,
+) -> Result<()> {
+    if segments.is_empty() {
+        return Err(TomlExtractError::InvalidFieldPath("Empty path".to_string()));
+    }
+    set_segment(current, segments, 0, new_value, create_missing)
+}
+
+/// Set the value below `current`, which is the value at `segments[..at]`
+fn set_segment(
+    current: &mut TomlValue,
+    segments: &[PathSegment],
+    at: usize,
+    new_value: TomlValue,
+    create_missing: bool,
+) -> Result<()> {
+    let is_last = at + 1 == segments.len();
+
+    let next = match &segments[at] {
+        PathSegment::Key(key) => {
+            if !current.is_table() {
+                if !create_missing {
+                    return Err(TomlExtractError::NotATable(format!(
+                        "Cannot set field {} on non-table value",
+                        format_path(&segments[..=at])
+                    )));
+                }
+                *current = TomlValue::Table(Table::new());
+            }
+            let table = current.as_table_mut().expect("checked above");
+            if is_last {
+                table.insert(key.clone(), new_value);
+                return Ok(());
+            }
+            table
+                .entry(key.clone())
+                .or_insert_with(|| empty_container(&segments[at + 1]))
+        }
+        PathSegment::Index(index) => {
+            if !current.is_array() {
+                if !create_missing {
+                    return Err(TomlExtractError::NotAnArray(format_path(&segments[..at])));
+                }
+                *current = TomlValue::Array(Vec::new());
+            }
+            let array = current.as_array_mut().expect("checked above");
+            if create_missing {
+                while array.len() <= *index {
+                    array.push(TomlValue::String(String::new()));
+                }
+            }
+            let length = array.len();
+            let elem =
+                array
+                    .get_mut(*index)
+                    .ok_or_else(|| TomlExtractError::ArrayIndexOutOfBounds {
+                        path: format_path(&segments[..at]),
+                        index: *index,
+                        length,
+                    })?;
+            if is_last {
+                *elem = new_value;
+                return Ok(());
+            }
+            elem
+        }
+    };
+
+    set_segment(next, segments, at + 1, new_value, create_missing)
+}
+
+/// Get a mutable reference to the value at `segments`, if it exists
+pub fn value_at_mut<'a>(
+    current: &'a mut TomlValue,
+    segments: &[PathSegment],
+) -> Option<&'a mut TomlValue> {
+    segments
+        .iter()
+        .try_fold(current, |value, segment| match segment {
+            PathSegment::Key(key) => value.as_table_mut()?.get_mut(key),
+            PathSegment::Index(index) => value.as_array_mut()?.get_mut(*index),
+        })
+}
+
+/// Remove the value at `segments` and return it
+///
+/// Removing an array element shifts the following elements down.
+///
+/// # Errors
+/// Returns [`TomlExtractError::InvalidFieldPath`] for an empty path and
+/// [`TomlExtractError::FieldNotFound`] or
+/// [`TomlExtractError::ArrayIndexOutOfBounds`] if there is nothing to remove.
+pub fn remove_value_at(root: &mut TomlValue, segments: &[PathSegment]) -> Result<TomlValue> {
+    let Some((last, parent_segments)) = segments.split_last() else {
+        return Err(TomlExtractError::InvalidFieldPath(
+            "Cannot remove the document root".to_string(),
+        ));
+    };
+    crate::get::utils::get_value_at(root, segments)?;
+
+    let parent = value_at_mut(root, parent_segments).expect("resolved above");
+    let removed = match (last, parent) {
+        (PathSegment::Key(key), TomlValue::Table(table)) => table.remove(key),
+        (PathSegment::Index(index), TomlValue::Array(array)) => Some(array.remove(*index)),
+        _ => None,
+    };
+    Ok(removed.expect("resolved above"))
+}
+
+fn empty_container(next: &PathSegment) -> TomlValue {
+    match next {
+        PathSegment::Key(_) => TomlValue::Table(Table::new()),
+        PathSegment::Index(_) => TomlValue::Array(Vec::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_path() {
+        assert_eq!(
+            parse_path("bin[1].name").unwrap(),
+            [
+                PathSegment::Key("bin".to_string()),
+                PathSegment::Index(1),
+                PathSegment::Key("name".to_string())
+            ]
+        );
+        assert_eq!(
+            parse_path(r#"target."cfg(unix)".'a.b'[0][2]"#).unwrap(),
+            [
+                PathSegment::Key("target".to_string()),
+                PathSegment::Key("cfg(unix)".to_string()),
+                PathSegment::Key("a.b".to_string()),
+                PathSegment::Index(0),
+                PathSegment::Index(2)
+            ]
+        );
+        assert!(parse_path("").unwrap().is_empty());
+        assert!(parse_path("a..b").is_err());
+        assert!(parse_path("a.\"b").is_err());
+        assert!(parse_path("a[x]").is_err());
+    }
+
+    #[test]
+    fn test_format_path_round_trip() {
+        for path in [
+            "package.name",
+            "bin[1].name",
+            r#"target."cfg(unix)".deps"#,
+            "\"\"",
+        ] {
+            assert_eq!(format_path(&parse_path(path).unwrap()), path);
+        }
+        assert_eq!(format_key("say \"hi\""), r#""say \"hi\"""#);
+    }
+
+    #[test]
+    fn test_split_unquoted() {
+        assert_eq!(split_unquoted("a.b=x=y", '='), Some(("a.b", "x=y")));
+        assert_eq!(split_unquoted(r#""a=b"=x"#, '='), Some((r#""a=b""#, "x")));
+        assert_eq!(
+            split_unquoted(r#""a\"=b".'c=d'=x"#, '='),
+            Some((r#""a\"=b".'c=d'"#, "x"))
+        );
+        assert_eq!(split_unquoted("'a=b'", '='), None);
+    }
+
+    #[test]
+    fn test_set_value_at() {
+        let mut value = TomlValue::Table(Table::new());
+        let segments = parse_path("a.list[1].b").unwrap();
+        assert!(matches!(
+            set_value_at(&mut value, &segments, TomlValue::Integer(1), false),
+            Err(TomlExtractError::ArrayIndexOutOfBounds { .. })
+        ));
+        set_value_at(&mut value, &segments, TomlValue::Integer(1), true).unwrap();
+        assert_eq!(value["a"]["list"][1]["b"].as_integer(), Some(1));
+        assert_eq!(value["a"]["list"][0].as_str(), Some(""));
+    }
+}
@@ -0,0 +1,251 @@
+//! Core functionality for query commands
+
+use std::fmt::Write as _;
+use toml::Value as TomlValue;
+
+use crate::document::load_toml;
+use crate::error::{Result, TomlExtractError};
+use crate::flatten::inline_value;
+use crate::get::utils::{get_nested_value, type_name};
+use crate::path::format_key;
+
+/// Leaf values longer than this many characters are truncated in [`field_tree`]
+pub const TREE_VALUE_WIDTH: usize = 40;
+
+/// Check whether a field path exists in a TOML file
+///
+/// A missing key, an out-of-bounds index or a path that runs into a value of
+/// the wrong type (`package.name.x`, `package[0]`) yields `Ok(false)`; I/O,
+/// parse and path syntax errors are still returned as `Err`.
+pub fn field_exists(file_path: &str, field_path: &str) -> Result<bool> {
+    let value = load_toml(file_path)?;
+    match get_nested_value(&value, field_path) {
+        Ok(_) => Ok(true),
+        Err(e) if e.is_not_found() => Ok(false),
+        Err(TomlExtractError::TypeMismatch { .. } | TomlExtractError::NotAnArray(_)) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Get the TOML type name of the value at a field path
+///
+/// # Returns
+/// One of `string`, `integer`, `float`, `boolean`, `datetime`, `array` or `table`
+pub fn field_type(file_path: &str, field_path: &str) -> Result<&'static str> {
+    let value = load_toml(file_path)?;
+    Ok(type_name(get_nested_value(&value, field_path)?))
+}
+
+/// List the child keys of the table at a field path, in document order
+///
+/// An empty `field_path` lists the top-level keys.
+pub fn field_keys(file_path: &str, field_path: &str) -> Result<Vec<String>> {
+    let value = load_toml(file_path)?;
+    let field_value = get_nested_value(&value, field_path)?;
+
+    field_value
+        .as_table()
+        .map(|table| table.keys().cloned().collect())
+        .ok_or_else(|| TomlExtractError::TypeMismatch {
+            path: field_path.to_string(),
+            expected: "table".to_string(),
+            found: type_name(field_value).to_string(),
+        })
+}
+
+/// Get the length of the value at a field path
+///
+/// Tables report their number of keys, arrays their number of elements and
+/// strings their number of characters.
+pub fn field_len(file_path: &str, field_path: &str) -> Result<usize> {
+    let value = load_toml(file_path)?;
+    let field_value = get_nested_value(&value, field_path)?;
+
+    match field_value {
+        TomlValue::Table(table) => Ok(table.len()),
+        TomlValue::Array(array) => Ok(array.len()),
+        TomlValue::String(s) => Ok(s.chars().count()),
+        other => Err(TomlExtractError::TypeMismatch {
+            path: field_path.to_string(),
+            expected: "table, array or string".to_string(),
+            found: type_name(other).to_string(),
+        }),
+    }
+}
+
+/// Render the value at a field path as an indented tree
+///
+/// Tables and arrays show their size, leaves their type and a truncated
+/// value. With `max_depth`, containers below that depth are summarized
+/// without their children.
+pub fn field_tree(file_path: &str, field_path: &str, max_depth: Option<usize>) -> Result<String> {
+    let value = load_toml(file_path)?;
+    let label = if field_path.is_empty() {
+        file_path
+    } else {
+        field_path
+    };
+    Ok(render_tree(
+        label,
+        get_nested_value(&value, field_path)?,
+        max_depth,
+    ))
+}
+
+/// Render a value as a tree whose root line is labelled `label`
+pub fn render_tree(label: &str, value: &TomlValue, max_depth: Option<usize>) -> String {
+    let mut out = String::new();
+    writeln!(out, "{}", tree_node_label(label, value)).expect("writing to a String cannot fail");
+    render_tree_children(&mut out, "", value, 1, max_depth);
+    out
+}
+
+fn render_tree_children(
+    out: &mut String,
+    prefix: &str,
+    value: &TomlValue,
+    depth: usize,
+    max_depth: Option<usize>,
+) {
+    if max_depth.is_some_and(|max| depth > max) {
+        return;
+    }
+
+    let children: Vec<(String, &TomlValue)> = match value {
+        TomlValue::Table(table) => table
+            .iter()
+            .map(|(key, child)| (format_key(key), child))
+            .collect(),
+        TomlValue::Array(array) => array
+            .iter()
+            .enumerate()
+            .map(|(index, child)| (format!("[{}]", index), child))
+            .collect(),
+        _ => return,
+    };
+
+    let count = children.len();
+    for (i, (label, child)) in children.into_iter().enumerate() {
+        let last = i + 1 == count;
+        let (branch, indent) = if last {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+        writeln!(
+            out,
+            "{}{}{}",
+            prefix,
+            branch,
+            tree_node_label(&label, child)
+        )
+        .expect("writing to a String cannot fail");
+        render_tree_children(
+            out,
+            &format!("{}{}", prefix, indent),
+            child,
+            depth + 1,
+            max_depth,
+        );
+    }
+}
+
+fn tree_node_label(label: &str, value: &TomlValue) -> String {
+    let plural = |n: usize, noun: &str| format!("{} {}{}", n, noun, if n == 1 { "" } else { "s" });
+    match value {
+        TomlValue::Table(table) => format!("{} (table, {})", label, plural(table.len(), "key")),
+        TomlValue::Array(array) => format!("{} (array, {})", label, plural(array.len(), "item")),
+        leaf => {
+            let text = inline_value(leaf);
+            let text = if text.chars().count() > TREE_VALUE_WIDTH {
+                let cut: String = text.chars().take(TREE_VALUE_WIDTH - 1).collect();
+                format!("{}…", cut)
+            } else {
+                text
+            };
+            format!("{} ({}) = {}", label, type_name(leaf), text)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn sample() -> NamedTempFile {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(
+            temp_file,
+            "[package]\nname = \"tést\"\nversion = \"1.0.0\"\nauthors = [\"Alice\", \"Bob\"]\n\n[dependencies]\ntoml = \"0.8\"\nclap = \"4\""
+        )
+        .unwrap();
+        temp_file
+    }
+
+    #[test]
+    fn test_field_exists() {
+        let temp_file = sample();
+        let path = temp_file.path().to_str().unwrap();
+
+        assert!(field_exists(path, "package.name").unwrap());
+        assert!(!field_exists(path, "package.license").unwrap());
+        assert!(!field_exists(path, "package.authors[5]").unwrap());
+        assert!(!field_exists(path, "package.name.first").unwrap());
+        assert!(!field_exists(path, "package.authors.first").unwrap());
+        assert!(!field_exists(path, "package[0]").unwrap());
+    }
+
+    #[test]
+    fn test_field_type_and_keys() {
+        let temp_file = sample();
+        let path = temp_file.path().to_str().unwrap();
+
+        assert_eq!(field_type(path, "package.authors").unwrap(), "array");
+        assert_eq!(field_type(path, "dependencies").unwrap(), "table");
+        assert_eq!(field_keys(path, "dependencies").unwrap(), ["toml", "clap"]);
+        assert_eq!(field_keys(path, "").unwrap(), ["package", "dependencies"]);
+        assert!(matches!(
+            field_keys(path, "package.name"),
+            Err(TomlExtractError::TypeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_field_len() {
+        let temp_file = sample();
+        let path = temp_file.path().to_str().unwrap();
+
+        assert_eq!(field_len(path, "package").unwrap(), 3);
+        assert_eq!(field_len(path, "package.authors").unwrap(), 2);
+        assert_eq!(field_len(path, "package.name").unwrap(), 4);
+    }
+
+    #[test]
+    fn test_field_tree() {
+        let temp_file = sample();
+        let path = temp_file.path().to_str().unwrap();
+
+        assert_eq!(
+            field_tree(path, "package", None).unwrap(),
+            "package (table, 3 keys)\n\
+             ├── name (string) = \"tést\"\n\
+             ├── version (string) = \"1.0.0\"\n\
+             └── authors (array, 2 items)\n\
+             \x20   ├── [0] (string) = \"Alice\"\n\
+             \x20   └── [1] (string) = \"Bob\"\n"
+        );
+        assert_eq!(
+            field_tree(path, "", Some(1))
+                .unwrap()
+                .lines()
+                .skip(1)
+                .collect::<Vec<_>>(),
+            [
+                "├── package (table, 3 keys)",
+                "└── dependencies (table, 2 keys)"
+            ]
+        );
+    }
+}
@@ -0,0 +1,14 @@
+//! Structural queries on TOML files
+//!
+//! Small probes built on [`get_nested_value`](crate::get::get_nested_value):
+//! whether a path exists, the type at a path, a table's keys, a value's length
+//! and a tree overview.
+
+pub mod core;
+#[cfg(feature = "cli")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cli")))]
+pub mod xcli;
+
+pub use core::*;
+#[cfg(feature = "cli")]
+pub use xcli::*;
@@ -0,0 +1,115 @@
+//! CLI definitions and handling for query commands
+
+use clap::{Arg, ArgMatches, Command};
+
+use super::core::*;
+use crate::error::Result;
+
+fn file_arg() -> Arg {
+    Arg::new("file")
+        .short('f')
+        .long("file")
+        .value_name("FILE")
+        .help("TOML file path")
+        .default_value("Cargo.toml")
+}
+
+fn path_arg(required: bool) -> Arg {
+    Arg::new("path")
+        .value_name("PATH")
+        .help("Dot-separated field path (e.g., package.name, authors[0], bin[1].name)")
+        .required(required)
+}
+
+/// Define the exists command CLI structure
+pub fn exists_command() -> Command {
+    Command::new("exists")
+        .about("Check whether a field exists (answers through the exit status only)")
+        .arg(path_arg(true))
+        .arg(file_arg())
+}
+
+/// Define the type command CLI structure
+pub fn type_command() -> Command {
+    Command::new("type")
+        .about(
+            "Print the type of a field (string, integer, float, boolean, datetime, array, table)",
+        )
+        .arg(path_arg(true))
+        .arg(file_arg())
+}
+
+/// Define the keys command CLI structure
+pub fn keys_command() -> Command {
+    Command::new("keys")
+        .about("List a table's child keys in document order (top-level keys if PATH is omitted)")
+        .arg(path_arg(false))
+        .arg(file_arg())
+}
+
+/// Define the len command CLI structure
+pub fn len_command() -> Command {
+    Command::new("len")
+        .about("Print the length of a table, array or string")
+        .arg(path_arg(true))
+        .arg(file_arg())
+}
+
+/// Define the tree command CLI structure
+pub fn tree_command() -> Command {
+    Command::new("tree")
+        .about("Print an indented tree of tables and arrays with each leaf's type and value")
+        .arg(path_arg(false))
+        .arg(file_arg())
+        .arg(
+            Arg::new("depth")
+                .short('d')
+                .long("depth")
+                .value_name("N")
+                .help("Only descend N levels below PATH")
+                .value_parser(clap::value_parser!(usize)),
+        )
+}
+
+fn file_and_path(matches: &ArgMatches) -> (&str, &str) {
+    let file_path = matches.get_one::<String>("file").unwrap();
+    let field_path = matches
+        .get_one::<String>("path")
+        .map(String::as_str)
+        .unwrap_or("");
+    (file_path, field_path)
+}
+
+/// Handle exists command logic
+///
+/// Prints nothing; the caller turns `Ok(false)` into exit status 1.
+pub fn handle_exists_command(matches: &ArgMatches) -> Result<bool> {
+    let (file_path, field_path) = file_and_path(matches);
+    field_exists(file_path, field_path)
+}
+
+/// Handle the type, keys, len and tree commands
+pub fn handle_query_command(name: &str, matches: &ArgMatches) -> Result<()> {
+    let (file_path, field_path) = file_and_path(matches);
+
+    match name {
+        "type" => println!("{}", field_type(file_path, field_path)?),
+        "keys" => {
+            for key in field_keys(file_path, field_path)? {
+                println!("{}", key);
+            }
+        }
+        "len" => println!("{}", field_len(file_path, field_path)?),
+        "tree" => print!(
+            "{}",
+            field_tree(
+                file_path,
+                field_path,
+                matches.get_one::<usize>("depth").copied()
+            )?
+        ),
+        other => unreachable!("unknown query command: {}", other),
+    }
+
+    Ok(())
+}
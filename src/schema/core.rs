@@ -0,0 +1,272 @@
+//! Core functionality for schema command
+//!
+//! Inference walks sample documents and merges what it sees at each path
+//! into a [`Shape`]: the types found there, how often a key was present in
+//! the tables that could hold it, the distinct strings and the shape of
+//! array elements. The shape is then written out as a JSON Schema that
+//! [`validate`](crate::validate) accepts.
+
+use toml::Value as TomlValue;
+
+use crate::document::load_toml;
+use crate::error::Result;
+use crate::get::utils::{get_nested_value, type_name};
+
+/// What was observed at one position across sample documents
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Shape {
+    /// Number of values seen here
+    pub count: usize,
+    /// TOML types seen (as named by `type`), in order of first appearance
+    pub types: Vec<&'static str>,
+    /// Distinct strings seen, in order of first appearance
+    pub strings: Vec<String>,
+    /// Number of strings seen
+    pub string_count: usize,
+    /// Number of tables seen
+    pub table_count: usize,
+    /// Keys of those tables, in order of first appearance
+    pub properties: Vec<(String, Shape)>,
+    /// Shape of array elements, once a non-empty array was seen
+    pub items: Option<Box<Shape>>,
+}
+
+impl Shape {
+    /// Merge one more value into the shape
+    pub fn observe(&mut self, value: &TomlValue) {
+        self.count += 1;
+        let name = type_name(value);
+        if !self.types.contains(&name) {
+            self.types.push(name);
+        }
+
+        match value {
+            TomlValue::String(s) => {
+                self.string_count += 1;
+                if !self.strings.contains(s) {
+                    self.strings.push(s.clone());
+                }
+            }
+            TomlValue::Table(table) => {
+                self.table_count += 1;
+                for (key, child) in table {
+                    let index = match self.properties.iter().position(|(k, _)| k == key) {
+                        Some(index) => index,
+                        None => {
+                            self.properties.push((key.clone(), Shape::default()));
+                            self.properties.len() - 1
+                        }
+                    };
+                    self.properties[index].1.observe(child);
+                }
+            }
+            TomlValue::Array(array) => {
+                let items = self.items.get_or_insert_with(Default::default);
+                for item in array {
+                    items.observe(item);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether a key of this table shape was present in every table seen
+    pub fn is_required(&self, child: &Shape) -> bool {
+        child.count == self.table_count
+    }
+
+    /// The distinct strings, if few enough and each repeated often enough
+    /// to look like a closed set: at most `max` values, and more
+    /// occurrences than values
+    pub fn enum_candidates(&self, max: usize) -> Option<&[String]> {
+        let only_strings = self.types == ["string"];
+        let distinct = self.strings.len();
+        (only_strings && distinct > 0 && distinct <= max && self.string_count > distinct)
+            .then_some(self.strings.as_slice())
+    }
+}
+
+/// Infer the shape shared by several values
+pub fn infer_shape<'a>(samples: impl IntoIterator<Item = &'a TomlValue>) -> Shape {
+    let mut shape = Shape::default();
+    for sample in samples {
+        shape.observe(sample);
+    }
+    shape
+}
+
+/// Infer the shape of `field_path` (the whole document if empty) across TOML files
+///
+/// # Errors
+/// Every file must parse and contain `field_path`.
+pub fn infer_files(file_paths: &[String], field_path: &str) -> Result<Shape> {
+    let mut shape = Shape::default();
+    for file_path in file_paths {
+        let document = load_toml(file_path)?;
+        shape.observe(get_nested_value(&document, field_path)?);
+    }
+    Ok(shape)
+}
+
+/// Write a shape as a JSON Schema (draft 2020-12)
+///
+/// Integers and floats seen at one place merge into `number`, and datetimes
+/// are strings, as they are for [`validate`](crate::validate). Strings get an
+/// `enum` when [`Shape::enum_candidates`] finds one for `enum_max`.
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub fn shape_to_schema(shape: &Shape, enum_max: usize) -> serde_json::Value {
+    let mut schema = serde_json::Map::new();
+    schema.insert(
+        "$schema".to_string(),
+        "https://json-schema.org/draft/2020-12/schema".into(),
+    );
+    schema.extend(schema_object(shape, enum_max));
+    serde_json::Value::Object(schema)
+}
+
+#[cfg(feature = "json")]
+fn schema_object(shape: &Shape, enum_max: usize) -> serde_json::Map<String, serde_json::Value> {
+    use serde_json::Value as JsonValue;
+
+    let mut types: Vec<&str> = Vec::new();
+    for name in &shape.types {
+        let json_type = match *name {
+            "table" => "object",
+            "float" => "number",
+            "datetime" => "string",
+            other => other,
+        };
+        if !types.contains(&json_type) {
+            types.push(json_type);
+        }
+    }
+    if types.contains(&"number") {
+        types.retain(|name| *name != "integer");
+    }
+
+    let mut schema = serde_json::Map::new();
+    match types.as_slice() {
+        [] => {}
+        [single] => {
+            schema.insert("type".to_string(), (*single).into());
+        }
+        several => {
+            schema.insert("type".to_string(), several.to_vec().into());
+        }
+    }
+
+    if let Some(values) = shape.enum_candidates(enum_max) {
+        schema.insert("enum".to_string(), values.to_vec().into());
+    }
+
+    if shape.table_count > 0 {
+        let mut properties = serde_json::Map::new();
+        let mut required = Vec::new();
+        for (key, child) in &shape.properties {
+            properties.insert(
+                key.clone(),
+                JsonValue::Object(schema_object(child, enum_max)),
+            );
+            if shape.is_required(child) {
+                required.push(JsonValue::String(key.clone()));
+            }
+        }
+        schema.insert("properties".to_string(), JsonValue::Object(properties));
+        if !required.is_empty() {
+            schema.insert("required".to_string(), JsonValue::Array(required));
+        }
+    }
+
+    if let Some(items) = shape.items.as_deref().filter(|items| items.count > 0) {
+        schema.insert(
+            "items".to_string(),
+            JsonValue::Object(schema_object(items, enum_max)),
+        );
+    }
+
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples() -> Vec<TomlValue> {
+        [
+            "name = \"api\"\nport = 8080\nmode = \"prod\"\ntags = [\"a\", \"b\"]\n[db]\nurl = \"pg://\"",
+            "name = \"web\"\nport = 80\nmode = \"prod\"\nratio = 0.5\n[db]\nurl = \"pg://\"\npool = 4",
+            "name = \"job\"\nport = 81.5\nmode = \"dev\"\ntags = []\n[[db]]\nurl = \"x\"",
+        ]
+        .iter()
+        .map(|s| toml::from_str(s).unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn test_infer_shape() {
+        let samples = samples();
+        let shape = infer_shape(&samples);
+        assert_eq!(shape.count, 3);
+        let keys: Vec<(&str, bool)> = shape
+            .properties
+            .iter()
+            .map(|(key, child)| (key.as_str(), shape.is_required(child)))
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                ("name", true),
+                ("port", true),
+                ("mode", true),
+                ("tags", false),
+                ("db", true),
+                ("ratio", false)
+            ]
+        );
+
+        let mode = &shape.properties[2].1;
+        assert_eq!(
+            mode.enum_candidates(5),
+            Some(&["prod".to_string(), "dev".to_string()][..])
+        );
+        assert_eq!(shape.properties[0].1.enum_candidates(5), None);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_shape_to_schema() {
+        let samples = samples();
+        let schema = shape_to_schema(&infer_shape(&samples), 5);
+        assert_eq!(
+            schema["required"],
+            serde_json::json!(["name", "port", "mode", "db"])
+        );
+        assert_eq!(schema["properties"]["port"]["type"], "number");
+        assert_eq!(
+            schema["properties"]["mode"]["enum"],
+            serde_json::json!(["prod", "dev"])
+        );
+        assert_eq!(
+            schema["properties"]["tags"],
+            serde_json::json!({"type": "array", "items": {"type": "string"}})
+        );
+        assert_eq!(
+            schema["properties"]["db"]["type"],
+            serde_json::json!(["object", "array"])
+        );
+        assert_eq!(
+            schema["properties"]["db"]["properties"]["pool"],
+            serde_json::json!({"type": "integer"})
+        );
+
+        // The samples themselves satisfy the inferred schema
+        #[cfg(feature = "validate")]
+        for sample in &samples {
+            let value = crate::get::utils::to_json_value(sample).unwrap();
+            assert!(crate::validate::validate_value(&value, &schema)
+                .unwrap()
+                .is_empty());
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! CLI definitions and handling for schema command
+
+use clap::{Arg, ArgMatches, Command};
+
+use super::core::*;
+use crate::document::write_file;
+use crate::error::Result;
+
+/// Define the schema command CLI structure
+pub fn schema_command() -> Command {
+    Command::new("schema")
+        .about("Work with JSON Schemas for TOML files")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("infer")
+                .about("Infer a JSON Schema from sample TOML files")
+                .arg(
+                    Arg::new("files")
+                        .value_name("FILE")
+                        .help("Sample TOML files")
+                        .num_args(1..)
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("field")
+                        .short('k')
+                        .long("field")
+                        .value_name("FIELD")
+                        .help("Field path of the subtree to describe (the whole document if omitted)")
+                        .default_value(""),
+                )
+                .arg(
+                    Arg::new("enum-max")
+                        .long("enum-max")
+                        .value_name("N")
+                        .help("Turn strings with at most N distinct, repeated values into an enum (0 disables)")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("5"),
+                )
+                .arg(
+                    Arg::new("output-file")
+                        .short('o')
+                        .long("output-file")
+                        .value_name("FILE")
+                        .help("Write the schema to FILE instead of stdout"),
+                ),
+        )
+}
+
+/// Handle schema command logic
+pub fn handle_schema_command(matches: &ArgMatches) -> Result<()> {
+    let Some(("infer", matches)) = matches.subcommand() else {
+        unreachable!("a subcommand is required");
+    };
+    let files: Vec<String> = matches
+        .get_many::<String>("files")
+        .unwrap()
+        .cloned()
+        .collect();
+    let shape = infer_files(&files, matches.get_one::<String>("field").unwrap())?;
+    let schema = shape_to_schema(&shape, *matches.get_one::<usize>("enum-max").unwrap());
+    let output = format!("{}\n", serde_json::to_string_pretty(&schema)?);
+
+    match matches.get_one::<String>("output-file") {
+        Some(output_file) => write_file(output_file, &output),
+        None => {
+            print!("{}", output);
+            Ok(())
+        }
+    }
+}
@@ -5,9 +5,12 @@ pub mod types;
 pub mod utils;
 
 /// CLI definitions and handling for set command.
+#[cfg(feature = "cli")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cli")))]
 pub mod xcli;
 
 pub use core::*;
 pub use types::*;
 pub use utils::*;
+#[cfg(feature = "cli")]
 pub use xcli::*;
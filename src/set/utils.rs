@@ -103,6 +103,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_parse_value_with_type() {
         assert!(matches!(
             parse_value_with_type("42", Some("integer")).unwrap(),
@@ -1,8 +1,8 @@
 // src/set/xcli.rs
-use anyhow::{Context, Result};
 use clap::{Arg, Command};
 // use crate::{SetConfig, set_field, set_field_and_save};
 use crate::{
+    error::Result,
     set::core::{set_field, set_field_and_save},
     SetConfig,
 };
@@ -58,6 +58,13 @@ pub fn cli() -> Command {
                 .help("Modify the file in place")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("quiet")
+                .short('q')
+                .long("quiet")
+                .help("Suppress status and error messages (the exit status still reports failures)")
+                .action(clap::ArgAction::SetTrue),
+        )
 }
 
 /// 实现 set 命令的处理逻辑
@@ -65,18 +72,19 @@ pub fn handle_set_command(matches: &clap::ArgMatches) -> Result<()> {
     // 解析参数
     let file_path = matches
         .get_one::<String>("file")
-        .context("File path is required")?;
+        .expect("File path is required");
     let field_path = matches
         .get_one::<String>("field")
-        .context("Field path is required")?;
+        .expect("Field path is required");
     let value = matches
         .get_one::<String>("value")
-        .context("Value is required")?;
+        .expect("Value is required");
     let value_type = matches
         .get_one::<String>("type")
-        .context("Value type is required")?;
+        .expect("Value type is required");
     let create_missing = matches.get_flag("create-missing");
     let in_place = matches.get_flag("in-place");
+    let quiet = matches.get_flag("quiet");
 
     // 处理值类型（自动推断或指定类型）
     let value_type = if value_type == "auto" {
@@ -98,10 +106,12 @@ pub fn handle_set_command(matches: &clap::ArgMatches) -> Result<()> {
     if in_place {
         // 原地修改文件
         set_field_and_save(&config)?;
-        println!(
-            "✅ Field '{}' set to '{}' in {}",
-            field_path, value, file_path
-        );
+        if !quiet {
+            println!(
+                "✅ Field '{}' set to '{}' in {}",
+                field_path, value, file_path
+            );
+        }
     } else {
         // 输出修改后的内容（不修改原文件）
         let result = set_field(&config)?;
@@ -0,0 +1,340 @@
+//! Core functionality for sort command
+//!
+//! Sorting edits a [`toml_edit`] document, so comments and layout travel
+//! with the entries they precede. A pattern is a field path in the syntax of
+//! [`crate::path`] whose keys may contain `*` wildcards, each matching within
+//! one key: `*.dependencies` matches `workspace.dependencies` and
+//! `*dependencies` matches `dev-dependencies` and `build-dependencies`.
+
+use std::cmp::Ordering;
+use toml_edit::{Array, ArrayOfTables, DocumentMut, Item, Table, Value};
+
+use crate::document::{parse_toml, parse_toml_edit, read_file, write_file};
+use crate::error::{Result, TomlExtractError};
+use crate::fmt::core::{assign_positions, collect_positions, has_comment, renumber_tables};
+use crate::get::utils::get_nested_value;
+use crate::path::{format_path, parse_path, PathSegment};
+
+/// Sort everything matching `patterns` in a TOML file and write the result back
+pub fn sort_file(file_path: &str, patterns: &[String], by: Option<&str>) -> Result<()> {
+    let updated = sort_toml(file_path, patterns, by)?;
+    write_file(file_path, &updated)
+}
+
+/// Sort everything matching `patterns` in a TOML file and return the updated content
+///
+/// Tables have their keys sorted, arrays their elements, and arrays of
+/// tables (or of inline tables) are ordered by the value of the `by` field.
+///
+/// # Errors
+/// A plain path that does not exist fails as the path lookup would, usually
+/// with [`TomlExtractError::FieldNotFound`]; any other pattern that matches
+/// nothing fails with [`TomlExtractError::NoMatch`].
+pub fn sort_toml(file_path: &str, patterns: &[String], by: Option<&str>) -> Result<String> {
+    let content = read_file(file_path)?;
+    let mut document = parse_toml_edit(&content, file_path)?;
+
+    for pattern in patterns {
+        if sort_document(&mut document, pattern, by)? == 0 {
+            // Let the plain path lookup explain what is missing
+            if !pattern.contains('*') {
+                get_nested_value(&parse_toml(&content, file_path)?, pattern)?;
+            }
+            return Err(TomlExtractError::NoMatch(pattern.clone()));
+        }
+    }
+
+    Ok(document.to_string())
+}
+
+/// Sort every table or array matching `pattern`, returning how many matched
+pub fn sort_document(document: &mut DocumentMut, pattern: &str, by: Option<&str>) -> Result<usize> {
+    let mut matches = Vec::new();
+    collect_matches(
+        document.as_item(),
+        &parse_path(pattern)?,
+        &mut Vec::new(),
+        &mut matches,
+    );
+
+    for segments in &matches {
+        let mut item = document.as_item_mut();
+        for segment in segments {
+            item = match segment {
+                PathSegment::Key(key) => item.get_mut(key.as_str()),
+                PathSegment::Index(index) => item.get_mut(*index),
+            }
+            .expect("matched above");
+        }
+        sort_item(item, by, segments)?;
+    }
+    Ok(matches.len())
+}
+
+fn collect_matches(
+    item: &Item,
+    pattern: &[PathSegment],
+    segments: &mut Vec<PathSegment>,
+    matches: &mut Vec<Vec<PathSegment>>,
+) {
+    let Some((first, rest)) = pattern.split_first() else {
+        matches.push(segments.clone());
+        return;
+    };
+
+    let children: Vec<(PathSegment, &Item)> = match first {
+        PathSegment::Key(glob) if glob.contains('*') => item
+            .as_table_like()
+            .map(|table| {
+                table
+                    .iter()
+                    .filter(|(key, _)| glob_match(glob, key))
+                    .map(|(key, child)| (PathSegment::Key(key.to_string()), child))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        PathSegment::Key(key) => item
+            .get(key.as_str())
+            .map(|child| (first.clone(), child))
+            .into_iter()
+            .collect(),
+        PathSegment::Index(index) => item
+            .get(*index)
+            .map(|child| (first.clone(), child))
+            .into_iter()
+            .collect(),
+    };
+
+    for (segment, child) in children {
+        segments.push(segment);
+        collect_matches(child, rest, segments, matches);
+        segments.pop();
+    }
+}
+
+/// Match a key against a pattern where `*` stands for any run of characters
+fn glob_match(pattern: &str, key: &str) -> bool {
+    let Some((prefix, rest)) = pattern.split_once('*') else {
+        return pattern == key;
+    };
+    let Some(tail) = key.strip_prefix(prefix) else {
+        return false;
+    };
+    tail.char_indices()
+        .map(|(i, _)| i)
+        .chain([tail.len()])
+        .any(|i| glob_match(rest, &tail[i..]))
+}
+
+fn sort_item(item: &mut Item, by: Option<&str>, segments: &[PathSegment]) -> Result<()> {
+    match item {
+        Item::Table(table) => sort_table_keys(table),
+        Item::Value(Value::InlineTable(table)) => table.sort_values(),
+        Item::Value(Value::Array(array)) => {
+            if by.is_some() && !array.iter().all(Value::is_inline_table) {
+                return Err(TomlExtractError::InvalidArgument(format!(
+                    "{} is not an array of tables; --by needs an array of tables",
+                    format_path(segments)
+                )));
+            }
+            sort_array(array, by)
+        }
+        Item::ArrayOfTables(array) => {
+            let field = by.ok_or_else(|| {
+                TomlExtractError::InvalidArgument(format!(
+                    "{} is an array of tables; choose a field to sort by",
+                    format_path(segments)
+                ))
+            })?;
+            sort_array_of_tables(array, field);
+        }
+        other => {
+            return Err(TomlExtractError::TypeMismatch {
+                path: format_path(segments),
+                expected: "table or array".to_string(),
+                found: other.type_name().to_string(),
+            })
+        }
+    }
+    Ok(())
+}
+
+/// Sort a table's keys, moving sub-table headers along
+fn sort_table_keys(table: &mut Table) {
+    table.sort_values();
+    renumber_tables(table);
+
+    // The new first entry should follow the header directly
+    for (mut key, item) in table.iter_mut() {
+        if item.is_value() {
+            let decor = key.leaf_decor_mut();
+            let prefix = decor
+                .prefix()
+                .and_then(|raw| raw.as_str())
+                .unwrap_or_default();
+            decor.set_prefix(prefix.trim_start_matches('\n').to_string());
+            break;
+        }
+    }
+}
+
+/// Sort array elements, or inline tables by their `by` field
+///
+/// Without comments each position keeps its own spacing, so a one-line
+/// array stays `["a", "b"]`; otherwise comments move with their elements.
+fn sort_array(array: &mut Array, by: Option<&str>) {
+    let decors: Vec<_> = array.iter().map(|value| value.decor().clone()).collect();
+    array.sort_by(|a, b| compare_values(sort_key(a, by), sort_key(b, by)));
+
+    if !decors.iter().any(has_comment) {
+        for (value, decor) in array.iter_mut().zip(decors) {
+            *value.decor_mut() = decor;
+        }
+    }
+}
+
+fn sort_key<'a>(value: &'a Value, by: Option<&str>) -> Option<&'a Value> {
+    match by {
+        Some(field) => value.as_inline_table().and_then(|table| table.get(field)),
+        None => Some(value),
+    }
+}
+
+/// Order `[[tables]]` by a field, reusing the header positions they occupy
+fn sort_array_of_tables(array: &mut ArrayOfTables, field: &str) {
+    let mut positions = Vec::new();
+    for table in array.iter() {
+        positions.extend(table.position());
+        collect_positions(table, &mut positions);
+    }
+    positions.sort_unstable();
+
+    let mut tables: Vec<Table> = array.iter().cloned().collect();
+    tables.sort_by(|a, b| {
+        compare_values(
+            a.get(field).and_then(Item::as_value),
+            b.get(field).and_then(Item::as_value),
+        )
+    });
+
+    let mut positions = positions.into_iter();
+    array.clear();
+    for mut table in tables {
+        if table.position().is_some() {
+            if let Some(position) = positions.next() {
+                table.set_position(position);
+            }
+        }
+        assign_positions(&mut table, &mut positions);
+        array.push(table);
+    }
+}
+
+/// Order values of the same type naturally and others by their TOML text;
+/// missing values sort last
+fn compare_values(a: Option<&Value>, b: Option<&Value>) -> Ordering {
+    match (a, b) {
+        (Some(Value::String(a)), Some(Value::String(b))) => a.value().cmp(b.value()),
+        (Some(Value::Integer(a)), Some(Value::Integer(b))) => a.value().cmp(b.value()),
+        (Some(Value::Float(a)), Some(Value::Float(b))) => a.value().total_cmp(b.value()),
+        (Some(a), Some(b)) => a.to_string().trim().cmp(b.to_string().trim()),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"[package]
+name = "demo"
+keywords = ["toml", "cli", "edit"]
+
+[dependencies]
+toml = "0.8" # parser
+# command line parsing
+clap = { version = "4", features = ["derive"] }
+anyhow = "1"
+
+[dev-dependencies]
+tempfile = "3"
+assert_cmd = "2"
+
+[[bin]]
+name = "zeta"
+
+[[bin]]
+# the main binary
+name = "alpha"
+path = "src/main.rs"
+"#;
+
+    fn sort(patterns: &[&str], by: Option<&str>) -> Result<String> {
+        let mut document: DocumentMut = SAMPLE.parse().unwrap();
+        for pattern in patterns {
+            if sort_document(&mut document, pattern, by)? == 0 {
+                panic!("{} matched nothing", pattern);
+            }
+        }
+        Ok(document.to_string())
+    }
+
+    #[test]
+    fn test_sort_tables_keeps_comments() {
+        let sorted = sort(&["*dependencies"], None).unwrap();
+        assert!(sorted.contains(
+            "[dependencies]\nanyhow = \"1\"\n# command line parsing\nclap = { version = \"4\", features = [\"derive\"] }\ntoml = \"0.8\" # parser\n"
+        ));
+        assert!(sorted.contains("[dev-dependencies]\nassert_cmd = \"2\"\ntempfile = \"3\"\n"));
+    }
+
+    #[test]
+    fn test_sort_arrays() {
+        let sorted = sort(&["package.keywords"], None).unwrap();
+        assert!(sorted.contains("keywords = [\"cli\", \"edit\", \"toml\"]"));
+
+        let sorted = sort(&["bin"], Some("name")).unwrap();
+        assert!(sorted.ends_with(
+            "[[bin]]\n# the main binary\nname = \"alpha\"\npath = \"src/main.rs\"\n\n[[bin]]\nname = \"zeta\"\n"
+        ));
+        assert!(matches!(
+            sort(&["bin"], None),
+            Err(TomlExtractError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            sort(&["package.name"], None),
+            Err(TomlExtractError::TypeMismatch { .. })
+        ));
+        let err = sort(&["package.keywords"], Some("name")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "package.keywords is not an array of tables; --by needs an array of tables"
+        );
+    }
+
+    #[test]
+    fn test_sort_toml_unmatched_patterns() {
+        let mut temp_file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut temp_file, SAMPLE.as_bytes()).unwrap();
+        let path = temp_file.path().to_str().unwrap();
+
+        match sort_toml(path, &["*.nothing".to_string()], None) {
+            Err(TomlExtractError::NoMatch(pattern)) => assert_eq!(pattern, "*.nothing"),
+            other => panic!("unexpected result: {other:?}"),
+        }
+        assert!(matches!(
+            sort_toml(path, &["package.nothing".to_string()], None),
+            Err(TomlExtractError::FieldNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*dependencies", "build-dependencies"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*c*", "abcbc"));
+        assert!(!glob_match("a*c", "abcb"));
+    }
+}
@@ -0,0 +1,68 @@
+//! CLI definitions and handling for sort command
+
+use clap::{Arg, ArgMatches, Command};
+
+use super::core::*;
+use crate::error::Result;
+
+/// Define the sort command CLI structure
+pub fn sort_command() -> Command {
+    Command::new("sort")
+        .about("Sort table keys or array elements in place, keeping comments with their entries")
+        .arg(
+            Arg::new("patterns")
+                .value_name("PATH")
+                .help("Table or array path; keys may use * wildcards (e.g. '*dependencies', 'target.*.dependencies')")
+                .num_args(1..)
+                .required(true),
+        )
+        .arg(
+            Arg::new("file")
+                .short('f')
+                .long("file")
+                .value_name("FILE")
+                .help("TOML file path")
+                .default_value("Cargo.toml"),
+        )
+        .arg(
+            Arg::new("by")
+                .long("by")
+                .value_name("FIELD")
+                .help("Order arrays of tables (e.g. bin) by this field; other arrays are an error"),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .help("Print the sorted document instead of writing it")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("quiet")
+                .short('q')
+                .long("quiet")
+                .help("Suppress status and error messages (the exit status still reports failures)")
+                .action(clap::ArgAction::SetTrue),
+        )
+}
+
+/// Handle sort command logic
+pub fn handle_sort_command(matches: &ArgMatches) -> Result<()> {
+    let file_path = matches.get_one::<String>("file").unwrap();
+    let patterns: Vec<String> = matches
+        .get_many::<String>("patterns")
+        .unwrap()
+        .cloned()
+        .collect();
+    let by = matches.get_one::<String>("by").map(String::as_str);
+
+    if matches.get_flag("dry-run") {
+        print!("{}", sort_toml(file_path, &patterns, by)?);
+        return Ok(());
+    }
+
+    sort_file(file_path, &patterns, by)?;
+    if !matches.get_flag("quiet") {
+        println!("✅ Sorted {}", file_path);
+    }
+    Ok(())
+}
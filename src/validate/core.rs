@@ -0,0 +1,830 @@
+//! Core functionality for validate command
+//!
+//! Documents are converted with [`to_json_value`] and checked against a JSON
+//! Schema by a small built-in validator. It covers the keywords config
+//! schemas rely on: `type`, `enum`, `const`, `properties`, `required`,
+//! `additionalProperties`, `patternProperties`, `propertyNames`, `items`,
+//! `prefixItems`, `contains`, the length, size and range limits, `pattern`,
+//! `multipleOf`, `allOf`, `anyOf`, `oneOf`, `not`, `if`/`then`/`else` and
+//! local `$ref`s (`#/$defs/...`). Annotations such as `format` are ignored.
+
+use regex::Regex;
+use serde_json::{Map, Value as JsonValue};
+use std::collections::HashMap;
+use std::str::FromStr;
+use toml_edit::ImDocument;
+
+use crate::document::{parse_json, parse_toml, read_file, read_input};
+use crate::error::{Result, SourceSpan, TomlExtractError};
+use crate::get::utils::to_json_value;
+use crate::path::{format_path, PathSegment};
+
+/// Schemas bundled with the tool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinSchema {
+    /// Cargo manifests (`Cargo.toml`)
+    Cargo,
+    /// Python project metadata (`pyproject.toml`)
+    Pyproject,
+}
+
+impl FromStr for BuiltinSchema {
+    type Err = TomlExtractError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "cargo" => Ok(Self::Cargo),
+            "pyproject" => Ok(Self::Pyproject),
+            other => Err(TomlExtractError::InvalidArgument(format!(
+                "Unknown builtin schema: {}",
+                other
+            ))),
+        }
+    }
+}
+
+impl BuiltinSchema {
+    /// Pick the bundled schema for a well-known file name
+    pub fn from_path(file_path: &str) -> Option<Self> {
+        match std::path::Path::new(file_path).file_name()?.to_str()? {
+            "Cargo.toml" => Some(Self::Cargo),
+            "pyproject.toml" => Some(Self::Pyproject),
+            _ => None,
+        }
+    }
+
+    /// The schema document
+    pub fn schema(self) -> JsonValue {
+        let source = match self {
+            Self::Cargo => include_str!("schemas/cargo.json"),
+            Self::Pyproject => include_str!("schemas/pyproject.json"),
+        };
+        serde_json::from_str(source).expect("bundled schemas are valid JSON")
+    }
+}
+
+/// Read a JSON Schema file (`-` for stdin)
+pub fn read_schema(schema_path: &str) -> Result<JsonValue> {
+    parse_json(&read_input(schema_path)?, schema_path)
+}
+
+/// A value that does not satisfy the schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// Field path in the syntax of [`crate::path`]; empty for the document root
+    pub path: String,
+    /// 1-based line of the offending key (or of its table for missing keys)
+    pub line: Option<usize>,
+    /// What is wrong
+    pub message: String,
+}
+
+/// Validate a TOML file against a schema, returning every violation with its line
+pub fn validate_file(file_path: &str, schema: &JsonValue) -> Result<Vec<Violation>> {
+    let content = read_file(file_path)?;
+    let document = to_json_value(&parse_toml(&content, file_path)?)?;
+
+    let mut violations = Vec::new();
+    Validator::new(schema).validate(&document, schema, &mut Vec::new(), &mut violations)?;
+
+    let spans = ImDocument::parse(content.as_str()).ok();
+    Ok(violations
+        .into_iter()
+        .map(|(segments, message)| Violation {
+            path: format_path(&segments),
+            line: spans
+                .as_ref()
+                .and_then(|spans| line_of(spans, &content, &segments)),
+            message,
+        })
+        .collect())
+}
+
+/// Validate a JSON value against a schema
+///
+/// # Errors
+/// Fails when the schema itself is unusable: an invalid `pattern` or a
+/// `$ref` that does not resolve within the schema.
+pub fn validate_value(value: &JsonValue, schema: &JsonValue) -> Result<Vec<Violation>> {
+    let mut violations = Vec::new();
+    Validator::new(schema).validate(value, schema, &mut Vec::new(), &mut violations)?;
+    Ok(violations
+        .into_iter()
+        .map(|(segments, message)| Violation {
+            path: format_path(&segments),
+            line: None,
+            message,
+        })
+        .collect())
+}
+
+/// Render violations as `file:line: path: message` lines
+pub fn render_violations(file_path: &str, violations: &[Violation]) -> String {
+    let mut out = String::new();
+    for violation in violations {
+        out.push_str(file_path);
+        if let Some(line) = violation.line {
+            out.push_str(&format!(":{}", line));
+        }
+        if !violation.path.is_empty() {
+            out.push_str(&format!(": {}", violation.path));
+        }
+        out.push_str(&format!(": {}\n", violation.message));
+    }
+    out
+}
+
+/// Line of the deepest key along `segments` that exists in the source
+fn line_of(document: &ImDocument<&str>, content: &str, segments: &[PathSegment]) -> Option<usize> {
+    let mut item = document.as_item();
+    let mut span = None;
+    for segment in segments {
+        let (child, child_span) = match segment {
+            PathSegment::Key(key) => {
+                match item.as_table_like().and_then(|t| t.get_key_value(key)) {
+                    Some((key, child)) => (child, key.span()),
+                    None => break,
+                }
+            }
+            PathSegment::Index(index) => match item.get(*index) {
+                Some(child) => (child, child.span()),
+                None => break,
+            },
+        };
+        item = child;
+        span = child_span.or(span);
+    }
+    span.map(|range| SourceSpan::from_range(content, range).line)
+}
+
+type Found = Vec<(Vec<PathSegment>, String)>;
+
+struct Validator<'s> {
+    root: &'s JsonValue,
+    patterns: HashMap<&'s str, Regex>,
+    /// `$ref` targets being followed, with the path depth they were entered at
+    refs: Vec<(&'s JsonValue, usize)>,
+}
+
+impl<'s> Validator<'s> {
+    fn new(root: &'s JsonValue) -> Self {
+        Self {
+            root,
+            patterns: HashMap::new(),
+            refs: Vec::new(),
+        }
+    }
+
+    fn is_valid(
+        &mut self,
+        value: &JsonValue,
+        schema: &'s JsonValue,
+        path: &mut Vec<PathSegment>,
+    ) -> Result<bool> {
+        let mut found = Vec::new();
+        self.validate(value, schema, path, &mut found)?;
+        Ok(found.is_empty())
+    }
+
+    fn validate(
+        &mut self,
+        value: &JsonValue,
+        schema: &'s JsonValue,
+        path: &mut Vec<PathSegment>,
+        found: &mut Found,
+    ) -> Result<()> {
+        let schema = match schema {
+            JsonValue::Bool(true) => return Ok(()),
+            JsonValue::Bool(false) => {
+                found.push((path.clone(), "no value is allowed here".to_string()));
+                return Ok(());
+            }
+            JsonValue::Object(schema) => schema,
+            _ => return Err(invalid_schema("a schema must be an object or a boolean")),
+        };
+        if let Some(reference) = schema.get("$ref").and_then(JsonValue::as_str) {
+            let target = self.resolve(reference)?;
+            // Following a `$ref` again without descending into the value would never end
+            let entry = (target, path.len());
+            if self
+                .refs
+                .iter()
+                .any(|&(seen, depth)| std::ptr::eq(seen, target) && depth == entry.1)
+            {
+                return Err(invalid_schema(&format!(
+                    "$ref {} refers back to itself",
+                    reference
+                )));
+            }
+            self.refs.push(entry);
+            let result = self.validate(value, target, path, found);
+            self.refs.pop();
+            result?;
+        }
+
+        if let Some(expected) = schema.get("type") {
+            let types: Vec<&str> = match expected {
+                JsonValue::String(name) => vec![name.as_str()],
+                JsonValue::Array(names) => names.iter().filter_map(JsonValue::as_str).collect(),
+                _ => return Err(invalid_schema("`type` must be a string or an array")),
+            };
+            if !types.iter().any(|name| has_type(value, name)) {
+                let expected: Vec<&str> = types.iter().map(|name| toml_type_name(name)).collect();
+                found.push((
+                    path.clone(),
+                    format!(
+                        "expected {}, found {}",
+                        expected.join(" or "),
+                        value_type_name(value)
+                    ),
+                ));
+                // Other keywords would only restate the type mismatch
+                return Ok(());
+            }
+        }
+
+        if let Some(JsonValue::Array(allowed)) = schema.get("enum") {
+            if !allowed.iter().any(|candidate| json_equal(candidate, value)) {
+                let allowed: Vec<String> = allowed.iter().map(JsonValue::to_string).collect();
+                found.push((
+                    path.clone(),
+                    format!("{} is not one of {}", value, allowed.join(", ")),
+                ));
+            }
+        }
+        if let Some(constant) = schema.get("const") {
+            if !json_equal(constant, value) {
+                found.push((
+                    path.clone(),
+                    format!("expected {}, found {}", constant, value),
+                ));
+            }
+        }
+
+        match value {
+            JsonValue::String(s) => self.validate_string(s, schema, path, found)?,
+            JsonValue::Number(n) => {
+                for message in number_violations(n.as_f64().unwrap_or(f64::NAN), schema) {
+                    found.push((path.clone(), message));
+                }
+            }
+            JsonValue::Array(items) => self.validate_array(items, schema, path, found)?,
+            JsonValue::Object(object) => self.validate_object(object, schema, path, found)?,
+            _ => {}
+        }
+
+        self.validate_combinators(value, schema, path, found)
+    }
+
+    fn validate_string(
+        &mut self,
+        s: &str,
+        schema: &'s Map<String, JsonValue>,
+        path: &[PathSegment],
+        found: &mut Found,
+    ) -> Result<()> {
+        let length = s.chars().count() as u64;
+        if let Some(min) = schema.get("minLength").and_then(JsonValue::as_u64) {
+            if length < min {
+                found.push((
+                    path.to_vec(),
+                    format!("string is shorter than {} characters", min),
+                ));
+            }
+        }
+        if let Some(max) = schema.get("maxLength").and_then(JsonValue::as_u64) {
+            if length > max {
+                found.push((
+                    path.to_vec(),
+                    format!("string is longer than {} characters", max),
+                ));
+            }
+        }
+        if let Some(pattern) = schema.get("pattern").and_then(JsonValue::as_str) {
+            if !self.regex(pattern)?.is_match(s) {
+                found.push((
+                    path.to_vec(),
+                    format!("{:?} does not match pattern {}", s, pattern),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_array(
+        &mut self,
+        items: &[JsonValue],
+        schema: &'s Map<String, JsonValue>,
+        path: &mut Vec<PathSegment>,
+        found: &mut Found,
+    ) -> Result<()> {
+        let count = items.len() as u64;
+        if let Some(min) = schema.get("minItems").and_then(JsonValue::as_u64) {
+            if count < min {
+                found.push((path.clone(), format!("array has fewer than {} items", min)));
+            }
+        }
+        if let Some(max) = schema.get("maxItems").and_then(JsonValue::as_u64) {
+            if count > max {
+                found.push((path.clone(), format!("array has more than {} items", max)));
+            }
+        }
+        if schema.get("uniqueItems") == Some(&JsonValue::Bool(true)) {
+            let duplicate = (1..items.len()).find(|&i| {
+                items[..i]
+                    .iter()
+                    .any(|earlier| json_equal(earlier, &items[i]))
+            });
+            if let Some(index) = duplicate {
+                found.push((
+                    path.clone(),
+                    format!("array items are not unique ({} repeats)", items[index]),
+                ));
+            }
+        }
+
+        let prefix = match schema.get("prefixItems") {
+            Some(JsonValue::Array(prefix)) => prefix.as_slice(),
+            _ => &[],
+        };
+        for (index, item) in items.iter().enumerate() {
+            let item_schema = match prefix.get(index) {
+                Some(item_schema) => item_schema,
+                None => match schema.get("items") {
+                    Some(item_schema) => item_schema,
+                    None => continue,
+                },
+            };
+            path.push(PathSegment::Index(index));
+            self.validate(item, item_schema, path, found)?;
+            path.pop();
+        }
+
+        if let Some(contains) = schema.get("contains") {
+            let mut any = false;
+            for (index, item) in items.iter().enumerate() {
+                path.push(PathSegment::Index(index));
+                any = self.is_valid(item, contains, path)?;
+                path.pop();
+                if any {
+                    break;
+                }
+            }
+            if !any {
+                found.push((
+                    path.clone(),
+                    "array has no item matching `contains`".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_object(
+        &mut self,
+        object: &Map<String, JsonValue>,
+        schema: &'s Map<String, JsonValue>,
+        path: &mut Vec<PathSegment>,
+        found: &mut Found,
+    ) -> Result<()> {
+        if let Some(JsonValue::Array(required)) = schema.get("required") {
+            for key in required.iter().filter_map(JsonValue::as_str) {
+                if !object.contains_key(key) {
+                    found.push((path.clone(), format!("missing required key `{}`", key)));
+                }
+            }
+        }
+        let count = object.len() as u64;
+        if let Some(min) = schema.get("minProperties").and_then(JsonValue::as_u64) {
+            if count < min {
+                found.push((path.clone(), format!("table has fewer than {} keys", min)));
+            }
+        }
+        if let Some(max) = schema.get("maxProperties").and_then(JsonValue::as_u64) {
+            if count > max {
+                found.push((path.clone(), format!("table has more than {} keys", max)));
+            }
+        }
+
+        let properties = schema.get("properties").and_then(JsonValue::as_object);
+        let pattern_properties = schema
+            .get("patternProperties")
+            .and_then(JsonValue::as_object);
+        for (key, child) in object {
+            path.push(PathSegment::Key(key.clone()));
+            if let Some(names) = schema.get("propertyNames") {
+                if !self.is_valid(&JsonValue::String(key.clone()), names, path)? {
+                    found.push((
+                        path.clone(),
+                        format!("key `{}` is not an allowed name", key),
+                    ));
+                }
+            }
+
+            let mut matched = false;
+            if let Some(child_schema) = properties.and_then(|properties| properties.get(key)) {
+                matched = true;
+                self.validate(child, child_schema, path, found)?;
+            }
+            for (pattern, child_schema) in pattern_properties.into_iter().flatten() {
+                if self.regex(pattern)?.is_match(key) {
+                    matched = true;
+                    self.validate(child, child_schema, path, found)?;
+                }
+            }
+            match schema.get("additionalProperties") {
+                Some(JsonValue::Bool(false)) if !matched => {
+                    path.pop();
+                    found.push((path.clone(), format!("unexpected key `{}`", key)));
+                    continue;
+                }
+                Some(additional) if !matched => self.validate(child, additional, path, found)?,
+                _ => {}
+            }
+            path.pop();
+        }
+        Ok(())
+    }
+
+    fn validate_combinators(
+        &mut self,
+        value: &JsonValue,
+        schema: &'s Map<String, JsonValue>,
+        path: &mut Vec<PathSegment>,
+        found: &mut Found,
+    ) -> Result<()> {
+        if let Some(JsonValue::Array(all)) = schema.get("allOf") {
+            for sub_schema in all {
+                self.validate(value, sub_schema, path, found)?;
+            }
+        }
+        if let Some(JsonValue::Array(any)) = schema.get("anyOf") {
+            let mut matched = false;
+            for sub_schema in any {
+                if self.is_valid(value, sub_schema, path)? {
+                    matched = true;
+                    break;
+                }
+            }
+            if !matched {
+                found.push((path.clone(), self.no_match_message(value, any, path)?));
+            }
+        }
+        if let Some(JsonValue::Array(one)) = schema.get("oneOf") {
+            let mut matches = 0;
+            for sub_schema in one {
+                if self.is_valid(value, sub_schema, path)? {
+                    matches += 1;
+                }
+            }
+            match matches {
+                0 => found.push((path.clone(), self.no_match_message(value, one, path)?)),
+                1 => {}
+                n => found.push((
+                    path.clone(),
+                    format!("value matches {} of the `oneOf` schemas instead of one", n),
+                )),
+            }
+        }
+        if let Some(not) = schema.get("not") {
+            if self.is_valid(value, not, path)? {
+                found.push((path.clone(), "value matches a `not` schema".to_string()));
+            }
+        }
+        if let Some(condition) = schema.get("if") {
+            let branch = if self.is_valid(value, condition, path)? {
+                schema.get("then")
+            } else {
+                schema.get("else")
+            };
+            if let Some(branch) = branch {
+                self.validate(value, branch, path, found)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Explain an `anyOf`/`oneOf` failure, quoting the single alternative
+    /// whose type fits when there is one
+    fn no_match_message(
+        &mut self,
+        value: &JsonValue,
+        alternatives: &'s [JsonValue],
+        path: &mut Vec<PathSegment>,
+    ) -> Result<String> {
+        let mut fitting = Vec::new();
+        for alternative in alternatives {
+            let mut found = Vec::new();
+            self.validate(value, alternative, path, &mut found)?;
+            if !found
+                .iter()
+                .any(|(_, message)| message.starts_with("expected "))
+            {
+                fitting.push(found);
+            }
+        }
+        Ok(match fitting.as_slice() {
+            [found] if !found.is_empty() => found
+                .iter()
+                .map(|(segments, message)| match segments.len() > path.len() {
+                    true => format!("{}: {}", format_path(segments), message),
+                    false => message.clone(),
+                })
+                .collect::<Vec<_>>()
+                .join("; "),
+            _ => format!("{} does not match any allowed form", value_type_name(value)),
+        })
+    }
+
+    fn resolve(&self, reference: &str) -> Result<&'s JsonValue> {
+        reference
+            .strip_prefix('#')
+            .and_then(|pointer| self.root.pointer(pointer))
+            .ok_or_else(|| invalid_schema(&format!("cannot resolve $ref {}", reference)))
+    }
+
+    fn regex(&mut self, pattern: &'s str) -> Result<&Regex> {
+        if !self.patterns.contains_key(pattern) {
+            let regex = Regex::new(pattern)
+                .map_err(|e| invalid_schema(&format!("invalid pattern {}: {}", pattern, e)))?;
+            self.patterns.insert(pattern, regex);
+        }
+        Ok(&self.patterns[pattern])
+    }
+}
+
+fn number_violations(n: f64, schema: &Map<String, JsonValue>) -> Vec<String> {
+    let limit = |name: &str| schema.get(name).and_then(JsonValue::as_f64);
+    let mut messages = Vec::new();
+    if let Some(min) = limit("minimum").filter(|&min| n < min) {
+        messages.push(format!("{} is less than the minimum {}", n, min));
+    }
+    if let Some(max) = limit("maximum").filter(|&max| n > max) {
+        messages.push(format!("{} is greater than the maximum {}", n, max));
+    }
+    if let Some(min) = limit("exclusiveMinimum").filter(|&min| n <= min) {
+        messages.push(format!("{} is not greater than {}", n, min));
+    }
+    if let Some(max) = limit("exclusiveMaximum").filter(|&max| n >= max) {
+        messages.push(format!("{} is not less than {}", n, max));
+    }
+    if let Some(step) = limit("multipleOf").filter(|&step| step > 0.0) {
+        if (n / step).fract() != 0.0 {
+            messages.push(format!("{} is not a multiple of {}", n, step));
+        }
+    }
+    messages
+}
+
+fn has_type(value: &JsonValue, name: &str) -> bool {
+    match (name, value) {
+        ("null", JsonValue::Null)
+        | ("boolean", JsonValue::Bool(_))
+        | ("number", JsonValue::Number(_))
+        | ("string", JsonValue::String(_))
+        | ("array", JsonValue::Array(_))
+        | ("object", JsonValue::Object(_)) => true,
+        ("integer", JsonValue::Number(n)) => {
+            n.is_i64() || n.is_u64() || n.as_f64().is_some_and(|f| f.fract() == 0.0)
+        }
+        _ => false,
+    }
+}
+
+/// JSON equality where `1` and `1.0` are the same number
+fn json_equal(a: &JsonValue, b: &JsonValue) -> bool {
+    match (a, b) {
+        (JsonValue::Number(a), JsonValue::Number(b)) => a.as_f64() == b.as_f64(),
+        (JsonValue::Array(a), JsonValue::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| json_equal(a, b))
+        }
+        (JsonValue::Object(a), JsonValue::Object(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, a)| b.get(key).is_some_and(|b| json_equal(a, b)))
+        }
+        _ => a == b,
+    }
+}
+
+/// Schema type names in the vocabulary of TOML
+fn toml_type_name(name: &str) -> &str {
+    match name {
+        "object" => "table",
+        other => other,
+    }
+}
+
+fn value_type_name(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::Bool(_) => "boolean",
+        JsonValue::Number(n) if n.is_f64() => "float",
+        JsonValue::Number(_) => "integer",
+        JsonValue::String(_) => "string",
+        JsonValue::Array(_) => "array",
+        JsonValue::Object(_) => "table",
+    }
+}
+
+fn invalid_schema(message: &str) -> TomlExtractError {
+    TomlExtractError::InvalidArgument(format!("Invalid schema: {}", message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_validate_value() {
+        let schema = json!({
+            "type": "object",
+            "required": ["name", "port"],
+            "properties": {
+                "name": {"type": "string", "pattern": "^[a-z]+$"},
+                "port": {"type": "integer", "minimum": 1, "maximum": 65535},
+                "mode": {"enum": ["dev", "prod"]},
+                "tags": {"type": "array", "items": {"$ref": "#/$defs/tag"}, "uniqueItems": true}
+            },
+            "additionalProperties": false,
+            "$defs": {"tag": {"type": "string", "maxLength": 3}}
+        });
+        let value = json!({
+            "name": "Api",
+            "mode": "test",
+            "tags": ["a", "long", "a"],
+            "extra": true
+        });
+
+        let messages: Vec<String> = validate_value(&value, &schema)
+            .unwrap()
+            .into_iter()
+            .map(|v| format!("{}: {}", v.path, v.message))
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                ": missing required key `port`",
+                "name: \"Api\" does not match pattern ^[a-z]+$",
+                "mode: \"test\" is not one of \"dev\", \"prod\"",
+                "tags: array items are not unique (\"a\" repeats)",
+                "tags[1]: string is longer than 3 characters",
+                ": unexpected key `extra`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_combinators() {
+        let schema = json!({
+            "anyOf": [{"type": "string"}, {"type": "object", "required": ["version"]}]
+        });
+        assert!(validate_value(&json!("1.0"), &schema).unwrap().is_empty());
+        let violations = validate_value(&json!({"path": "x"}), &schema).unwrap();
+        assert_eq!(violations[0].message, "missing required key `version`");
+        let violations = validate_value(&json!(3), &schema).unwrap();
+        assert_eq!(
+            violations[0].message,
+            "integer does not match any allowed form"
+        );
+
+        assert!(validate_value(&json!(1), &json!({"$ref": "#/nope"})).is_err());
+        assert!(validate_value(&json!("x"), &json!({"pattern": "("})).is_err());
+    }
+
+    #[test]
+    fn test_validate_refs() {
+        let tree = json!({
+            "$defs": {"node": {
+                "type": "object",
+                "properties": {"children": {"type": "array", "items": {"$ref": "#/$defs/node"}}},
+                "required": ["name"]
+            }},
+            "$ref": "#/$defs/node"
+        });
+        let value = json!({"name": "a", "children": [{"name": "b", "children": [{}]}]});
+        let violations = validate_value(&value, &tree).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].path, "children[0].children[0]");
+
+        for schema in [
+            json!({"$ref": "#"}),
+            json!({"anyOf": [{"$ref": "#"}]}),
+            json!({"$defs": {"a": {"$ref": "#/$defs/b"}, "b": {"$ref": "#/$defs/a"}}, "$ref": "#/$defs/a"}),
+        ] {
+            match validate_value(&json!(1), &schema) {
+                Err(TomlExtractError::InvalidArgument(message)) => {
+                    assert!(message.contains("refers back to itself"), "{}", message)
+                }
+                other => panic!("unexpected result: {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate_patterns_and_additional_properties() {
+        // `pattern` is not anchored
+        let schema = json!({"pattern": "a+"});
+        assert!(validate_value(&json!("cat"), &schema).unwrap().is_empty());
+        assert_eq!(validate_value(&json!("dog"), &schema).unwrap().len(), 1);
+
+        let schema = json!({
+            "properties": {"name": {"type": "string"}},
+            "patternProperties": {"^x-": {"type": "integer"}},
+            "additionalProperties": {"type": "boolean"}
+        });
+        let value = json!({"name": "a", "x-level": 1, "x-bad": "1", "debug": true, "other": 1});
+        let messages: Vec<String> = validate_value(&value, &schema)
+            .unwrap()
+            .into_iter()
+            .map(|v| format!("{}: {}", v.path, v.message))
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "x-bad: expected integer, found string",
+                "other: expected boolean, found integer",
+            ]
+        );
+
+        let schema = json!({"patternProperties": {"^x-": true}, "additionalProperties": false});
+        let violations = validate_value(&json!({"x-a": 1, "name": 2}), &schema).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].message, "unexpected key `name`");
+    }
+
+    #[test]
+    fn test_validate_file_reports_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("Cargo.toml");
+        std::fs::write(
+            &file,
+            "[package]\nname = \"demo\"\nedition = \"2020\"\n\n[dependencies]\nserde = { version = \"1\", feature = [\"derive\"] }\n",
+        )
+        .unwrap();
+        let file = file.to_str().unwrap();
+
+        let violations = validate_file(file, &BuiltinSchema::Cargo.schema()).unwrap();
+        assert_eq!(
+            render_violations("Cargo.toml", &violations),
+            "Cargo.toml:3: package.edition: \"2020\" is not one of \"2015\", \"2018\", \"2021\", \"2024\"\n\
+             Cargo.toml:6: dependencies.serde: unexpected key `feature`\n"
+        );
+        assert_eq!(BuiltinSchema::from_path(file), Some(BuiltinSchema::Cargo));
+    }
+
+    #[test]
+    fn test_cargo_schema_accepts_real_manifest() {
+        let manifest: toml::Value = toml::from_str(
+            r#"
+[package]
+name = "tokio-util"
+version.workspace = true
+edition = "2021"
+rust-version = "1.70"
+autolib = false
+autobins = false
+workspace = ".."
+readme = false
+publish = ["crates-io"]
+
+[lib]
+path = "src/lib.rs"
+doc-scrape-examples = true
+crate-type = ["rlib"]
+
+[[bin]]
+name = "tool"
+path = "src/main.rs"
+required-features = ["cli"]
+doc-scrape-examples = false
+
+[[example]]
+name = "demo"
+doc-scrape-examples = true
+
+[features]
+cli = ["dep:clap"]
+
+[dependencies]
+clap = { version = "4", optional = true, default-features = false }
+serde.workspace = true
+
+[target.'cfg(unix)'.dependencies]
+libc = "0.2"
+
+[package.metadata.docs.rs]
+all-features = true
+
+[lints.rust]
+unsafe_code = "forbid"
+"#,
+        )
+        .unwrap();
+        let value = serde_json::to_value(&manifest).unwrap();
+
+        let violations = validate_value(&value, &BuiltinSchema::Cargo.schema()).unwrap();
+        assert!(violations.is_empty(), "{:?}", violations);
+    }
+}
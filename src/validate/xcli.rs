@@ -0,0 +1,71 @@
+//! CLI definitions and handling for validate command
+
+use clap::{Arg, ArgMatches, Command};
+
+use super::core::*;
+use crate::error::{Result, TomlExtractError};
+
+/// Define the validate command CLI structure
+pub fn validate_command() -> Command {
+    Command::new("validate")
+        .about("Check a TOML file against a JSON Schema and report every violation with its line")
+        .arg(
+            Arg::new("file")
+                .short('f')
+                .long("file")
+                .value_name("FILE")
+                .help("TOML file path")
+                .default_value("Cargo.toml"),
+        )
+        .arg(
+            Arg::new("schema")
+                .short('s')
+                .long("schema")
+                .value_name("SCHEMA")
+                .help("JSON Schema file (- for stdin)")
+                .conflicts_with("builtin"),
+        )
+        .arg(
+            Arg::new("builtin")
+                .long("builtin")
+                .value_name("NAME")
+                .help("Bundled schema; picked from the file name (Cargo.toml, pyproject.toml) if neither option is given")
+                .value_parser(["cargo", "pyproject"]),
+        )
+        .arg(
+            Arg::new("quiet")
+                .short('q')
+                .long("quiet")
+                .help("Print nothing (the exit status still reports violations)")
+                .action(clap::ArgAction::SetTrue),
+        )
+}
+
+/// Handle validate command logic
+///
+/// Returns whether the file is valid; the caller turns `Ok(false)` into
+/// exit status 1.
+pub fn handle_validate_command(matches: &ArgMatches) -> Result<bool> {
+    let file_path = matches.get_one::<String>("file").unwrap();
+    let schema = match (
+        matches.get_one::<String>("schema"),
+        matches.get_one::<String>("builtin"),
+    ) {
+        (Some(schema_path), _) => read_schema(schema_path)?,
+        (None, Some(name)) => name.parse::<BuiltinSchema>()?.schema(),
+        (None, None) => BuiltinSchema::from_path(file_path)
+            .ok_or_else(|| {
+                TomlExtractError::InvalidArgument(format!(
+                    "No bundled schema for {}; pass --schema or --builtin",
+                    file_path
+                ))
+            })?
+            .schema(),
+    };
+
+    let violations = validate_file(file_path, &schema)?;
+    if !matches.get_flag("quiet") {
+        print!("{}", render_violations(file_path, &violations));
+    }
+    Ok(violations.is_empty())
+}